@@ -3,10 +3,11 @@ mod utils;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use ffi::*;
 use libc::size_t;
-use mdbx::{ObjectLength, WriteFlags};
+use mdbx::{DatabaseFlags, Environment, KeyBuf, NoWriteMap, ObjectLength, WriteFlags};
 use rand::{prelude::SliceRandom, SeedableRng};
 use rand_xorshift::XorShiftRng;
-use std::ptr;
+use std::{io::Write, ptr};
+use tempfile::tempdir;
 use utils::*;
 
 fn bench_get_rand(c: &mut Criterion) {
@@ -32,6 +33,29 @@ fn bench_get_rand(c: &mut Criterion) {
     });
 }
 
+fn bench_get_rand_local(c: &mut Criterion) {
+    let n = 100u32;
+    let (_dir, env) = setup_bench_db(n);
+    let txn = env.begin_ro_txn_local().unwrap();
+    let db = txn.open_db(None).unwrap();
+
+    let mut keys: Vec<String> = (0..n).map(get_key).collect();
+    keys.shuffle(&mut XorShiftRng::from_seed(Default::default()));
+
+    c.bench_function("bench_get_rand_local", |b| {
+        b.iter(|| {
+            let mut i = 0usize;
+            for key in &keys {
+                i += *txn
+                    .get::<ObjectLength>(&db, key.as_bytes())
+                    .unwrap()
+                    .unwrap();
+            }
+            black_box(i);
+        })
+    });
+}
+
 fn bench_get_rand_raw(c: &mut Criterion) {
     let n = 100u32;
     let (_dir, env) = setup_bench_db(n);
@@ -91,6 +115,69 @@ fn bench_put_rand(c: &mut Criterion) {
     });
 }
 
+fn bench_put_rand_key_buf(c: &mut Criterion) {
+    let n = 100u32;
+    let (_dir, env) = setup_bench_db(0);
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.prime_for_permaopen(db);
+    let db = txn.commit_and_rebind_open_dbs().unwrap().1.remove(0);
+
+    let mut ids: Vec<u32> = (0..n).collect();
+    ids.shuffle(&mut XorShiftRng::from_seed(Default::default()));
+
+    // Compare to formatting each key into a new String, as bench_put_rand does up front.
+    c.bench_function("bench_put_rand_format", |b| {
+        b.iter(|| {
+            let txn = env.begin_rw_txn().unwrap();
+            for &i in &ids {
+                txn.put(&db, get_key(i), get_data(i), WriteFlags::empty())
+                    .unwrap();
+            }
+        })
+    });
+
+    let mut key = KeyBuf::new();
+    let mut data = KeyBuf::new();
+    c.bench_function("bench_put_rand_key_buf", |b| {
+        b.iter(|| {
+            let txn = env.begin_rw_txn().unwrap();
+            for &i in &ids {
+                key.clear();
+                write!(key, "key{}", i).unwrap();
+                data.clear();
+                write!(data, "data{}", i).unwrap();
+                txn.put(&db, &key, &data, WriteFlags::empty()).unwrap();
+            }
+        })
+    });
+}
+
+fn bench_put_batch_rand(c: &mut Criterion) {
+    let n = 100u32;
+    let (_dir, env) = setup_bench_db(0);
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.prime_for_permaopen(db);
+    let db = txn.commit_and_rebind_open_dbs().unwrap().1.remove(0);
+
+    let mut items: Vec<(String, String)> = (0..n).map(|n| (get_key(n), get_data(n))).collect();
+    items.shuffle(&mut XorShiftRng::from_seed(Default::default()));
+    let pairs: Vec<(&[u8], &[u8])> = items
+        .iter()
+        .map(|(key, data)| (key.as_bytes(), data.as_bytes()))
+        .collect();
+
+    c.bench_function("bench_put_batch_rand", |b| {
+        b.iter(|| {
+            let txn = env.begin_rw_txn().unwrap();
+            txn.put_batch(&db, &pairs, WriteFlags::empty()).unwrap();
+        })
+    });
+}
+
 fn bench_put_rand_raw(c: &mut Criterion) {
     let n = 100u32;
     let (_dir, _env) = setup_bench_db(0);
@@ -130,11 +217,85 @@ fn bench_put_rand_raw(c: &mut Criterion) {
     });
 }
 
+fn bench_begin_ro(c: &mut Criterion) {
+    let (_dir, env) = setup_bench_db(0);
+
+    c.bench_function("bench_begin_ro", |b| {
+        b.iter(|| black_box(env.begin_ro_txn().unwrap().id()))
+    });
+}
+
+fn bench_begin_ro_open_db(c: &mut Criterion) {
+    let (_dir, env) = setup_bench_db(1);
+    let key = get_key(0);
+
+    c.bench_function("bench_begin_ro_open_db", |b| {
+        b.iter(|| {
+            let txn = env.begin_ro_txn().unwrap();
+            let db = txn.open_db(None).unwrap();
+            black_box(txn.get::<ObjectLength>(&db, key.as_bytes()).unwrap())
+        })
+    });
+
+    c.bench_function("bench_default_db_txn", |b| {
+        b.iter(|| {
+            let (txn, db) = env.default_db_txn().unwrap();
+            black_box(txn.get::<ObjectLength>(&db, key.as_bytes()).unwrap())
+        })
+    });
+}
+
+fn bench_begin_ro_open_named_db(c: &mut Criterion) {
+    for close_dbs in [false, true] {
+        let dir = tempdir().unwrap();
+        let env = Environment::<NoWriteMap>::new()
+            .set_max_dbs(1)
+            .set_close_dbs(close_dbs)
+            .open(dir.path())
+            .unwrap();
+        let txn = env.begin_rw_txn().unwrap();
+        txn.create_db(Some("named"), DatabaseFlags::empty())
+            .unwrap();
+        txn.commit().unwrap();
+
+        let name = if close_dbs {
+            "bench_begin_ro_open_named_db_close_dbs"
+        } else {
+            "bench_begin_ro_open_named_db"
+        };
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                let txn = env.begin_ro_txn().unwrap();
+                black_box(txn.open_db(Some("named")).unwrap().dbi())
+            })
+        });
+    }
+}
+
+fn bench_begin_ro_pooled(c: &mut Criterion) {
+    let dir = tempdir().unwrap();
+    let env = Environment::<NoWriteMap>::new()
+        .set_ro_txn_pool_size(1)
+        .open(dir.path())
+        .unwrap();
+
+    c.bench_function("bench_begin_ro_pooled", |b| {
+        b.iter(|| black_box(env.ro_txn().unwrap().id()))
+    });
+}
+
 criterion_group!(
     benches,
+    bench_begin_ro,
+    bench_begin_ro_open_db,
+    bench_begin_ro_open_named_db,
+    bench_begin_ro_pooled,
     bench_get_rand,
+    bench_get_rand_local,
     bench_get_rand_raw,
     bench_put_rand,
+    bench_put_rand_key_buf,
+    bench_put_batch_rand,
     bench_put_rand_raw
 );
 criterion_main!(benches);
@@ -0,0 +1,125 @@
+//! Derive macros for the [mdbx](https://docs.rs/mdbx) crate, enabled with its `derive` feature.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Error};
+
+/// Derives `Pod`, `TableObject` and `AsRef<[u8]>` for a struct, so that it is stored as its
+/// in-memory representation.
+///
+/// The struct must be `#[repr(C)]` (or `#[repr(transparent)]`), must not be generic, and all of
+/// its fields must implement `Pod`. It must not have any padding, which is checked at compile
+/// time. Decoding fails with `Error::DecodeError` if the stored value is not exactly the size of
+/// the struct, and doesn't require the value to be aligned.
+///
+/// ```
+/// use mdbx::{Environment, NoWriteMap, TableObject, WriteFlags};
+///
+/// #[derive(Debug, PartialEq, TableObject)]
+/// #[repr(C)]
+/// struct Point {
+///     x: u32,
+///     y: u32,
+/// }
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let env = Environment::<NoWriteMap>::new().open(dir.path()).unwrap();
+/// let txn = env.begin_rw_txn().unwrap();
+/// let db = txn.open_db(None).unwrap();
+/// txn.put(&db, b"origin", Point { x: 0, y: 0 }, WriteFlags::empty())
+///     .unwrap();
+/// assert_eq!(txn.get(&db, b"origin").unwrap(), Some(Point { x: 0, y: 0 }));
+/// ```
+///
+/// Fields that are not valid for every bit pattern are rejected:
+///
+/// ```compile_fail
+/// #[derive(mdbx::TableObject)]
+/// #[repr(C)]
+/// struct Flag {
+///     set: bool,
+/// }
+/// ```
+///
+/// And so is padding:
+///
+/// ```compile_fail
+/// #[derive(mdbx::TableObject)]
+/// #[repr(C)]
+/// struct Padded {
+///     a: u8,
+///     b: u32,
+/// }
+/// ```
+#[proc_macro_derive(TableObject)]
+pub fn derive_table_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_table_object(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_table_object(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "TableObject cannot be derived for generic types",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "TableObject can only be derived for structs",
+            ))
+        }
+    };
+    if !has_stable_layout(&input.attrs)? {
+        return Err(Error::new(
+            name.span(),
+            "TableObject can only be derived for #[repr(C)] or #[repr(transparent)] structs",
+        ));
+    }
+    let tys = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+
+    Ok(quote! {
+        const _: () = assert!(
+            ::std::mem::size_of::<#name>() == 0 #(+ ::std::mem::size_of::<#tys>())*,
+            "TableObject cannot be derived for structs with padding",
+        );
+
+        unsafe impl ::mdbx::Pod for #name where #(#tys: ::mdbx::Pod,)* {}
+
+        impl<'tx> ::mdbx::TableObject<'tx> for #name {
+            fn decode(data_val: &[u8]) -> ::std::result::Result<Self, ::mdbx::Error> {
+                <Self as ::mdbx::Pod>::from_bytes(data_val)
+            }
+        }
+
+        impl ::std::convert::AsRef<[u8]> for #name {
+            fn as_ref(&self) -> &[u8] {
+                ::mdbx::Pod::as_bytes(self)
+            }
+        }
+    })
+}
+
+/// Returns whether the struct is `#[repr(C)]` or `#[repr(transparent)]`, possibly along with other
+/// representation hints such as `align`.
+fn has_stable_layout(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut stable = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") || meta.path.is_ident("transparent") {
+                stable = true;
+            } else if meta.input.peek(syn::token::Paren) {
+                let _content;
+                syn::parenthesized!(_content in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+    Ok(stable)
+}
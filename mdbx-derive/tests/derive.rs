@@ -0,0 +1,63 @@
+use mdbx::{Environment, Error, NoWriteMap, Pod, TableObject, WriteFlags};
+use tempfile::tempdir;
+
+#[derive(Clone, Copy, Debug, PartialEq, TableObject)]
+#[repr(C)]
+struct Header {
+    version: u32,
+    flags: u16,
+    kind: [u8; 2],
+    size: u64,
+}
+
+#[derive(Debug, PartialEq, TableObject)]
+#[repr(transparent)]
+struct Wrapper(Header);
+
+#[test]
+fn test_round_trip() {
+    let dir = tempdir().unwrap();
+    let env = Environment::<NoWriteMap>::new().open(dir.path()).unwrap();
+
+    let header = Header {
+        version: 3,
+        flags: 0xbeef,
+        kind: *b"hd",
+        size: u64::MAX - 1,
+    };
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(&db, b"header", header, WriteFlags::empty())
+        .unwrap();
+    txn.put(&db, b"wrapper", Wrapper(header), WriteFlags::empty())
+        .unwrap();
+    txn.put(&db, b"short", [0u8; 8], WriteFlags::empty())
+        .unwrap();
+    txn.commit().unwrap();
+
+    let txn = env.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(txn.get(&db, b"header").unwrap(), Some(header));
+    assert_eq!(txn.get(&db, b"wrapper").unwrap(), Some(Wrapper(header)));
+    assert!(matches!(
+        txn.get::<Header>(&db, b"short"),
+        Err(Error::DecodeError(_))
+    ));
+}
+
+#[test]
+fn test_unaligned() {
+    let header = Header {
+        version: 1,
+        flags: 2,
+        kind: *b"ab",
+        size: 4,
+    };
+    let mut buf = vec![0u8; std::mem::size_of::<Header>() + 1];
+    buf[1..].copy_from_slice(header.as_bytes());
+    assert_eq!(Header::from_bytes(&buf[1..]).unwrap(), header);
+    assert_eq!(
+        Header::from_bytes(&buf).unwrap_err().to_string(),
+        "expected 16 bytes, got 17"
+    );
+}
@@ -1,7 +1,7 @@
 use crate::{error::mdbx_result, Error, TransactionKind};
 use derive_more::*;
 use lifetimed_bytes::Bytes;
-use std::{borrow::Cow, slice};
+use std::{borrow::Cow, io, mem, ops, ptr, slice, sync::Arc};
 use thiserror::Error;
 
 /// Implement this to be able to decode data values
@@ -24,6 +24,13 @@ pub trait TableObject<'tx> {
     }
 }
 
+/// Borrows the data straight from the memory map where that is safe, and copies it otherwise.
+///
+/// In a read-write transaction, data on a page that the transaction has already modified is
+/// always copied, since later writes in the same transaction may move or overwrite that page.
+/// Data on untouched pages belongs to a snapshot that stays intact until the transaction ends, so
+/// it can be borrowed even if the same key is written afterwards. Either way the value remains
+/// valid for the whole lifetime of the transaction.
 impl<'tx> TableObject<'tx> for Cow<'tx, [u8]> {
     fn decode(_: &[u8]) -> Result<Self, Error> {
         unreachable!()
@@ -69,6 +76,39 @@ impl<'tx> TableObject<'tx> for Vec<u8> {
     }
 }
 
+/// Copies the data into a single allocation of exactly its length.
+impl<'tx> TableObject<'tx> for Box<[u8]> {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Ok(Box::from(data_val))
+    }
+}
+
+/// Copies the data into a single allocation shared by all clones, without going through a
+/// [`Vec<u8>`] first.
+impl<'tx> TableObject<'tx> for Arc<[u8]> {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Ok(Arc::from(data_val))
+    }
+}
+
+/// Decodes the data as UTF-8, failing with [Error::DecodeError] if it is not valid.
+impl<'tx> TableObject<'tx> for String {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        String::from_utf8(data_val.to_vec()).map_err(|e| Error::DecodeError(Box::new(e)))
+    }
+}
+
+/// Presence-only decoding: succeeds for data of any length, including empty, without reading or
+/// copying it.
 impl<'tx> TableObject<'tx> for () {
     fn decode(_: &[u8]) -> Result<Self, Error> {
         Ok(())
@@ -95,18 +135,141 @@ impl<'tx> TableObject<'tx> for ObjectLength {
     }
 }
 
-impl<'tx, const LEN: usize> TableObject<'tx> for [u8; LEN] {
+/// A reusable buffer for building keys, to avoid allocating a new one for every item in a loop.
+///
+/// It can be passed by reference wherever a key is expected, e.g. to
+/// [Transaction::put()](crate::Transaction::put) and [Transaction::get()](crate::Transaction::get).
+/// Clearing it keeps the allocation, so after the first few items building a key doesn't allocate.
+/// The borrow checker ensures the buffer can't be changed while MDBX is reading it, and values
+/// returned by MDBX never point into it.
+///
+/// ```
+/// # use mdbx::{Environment, KeyBuf, NoWriteMap, WriteFlags};
+/// # let dir = tempfile::tempdir().unwrap();
+/// # let env = Environment::<NoWriteMap>::new().open(dir.path()).unwrap();
+/// use std::io::Write;
+///
+/// let txn = env.begin_rw_txn().unwrap();
+/// let db = txn.open_db(None).unwrap();
+/// let mut key = KeyBuf::new();
+/// for i in 0..10u32 {
+///     key.clear().extend(b"user/").extend(i.to_be_bytes());
+///     txn.put(&db, &key, b"", WriteFlags::empty()).unwrap();
+/// }
+///
+/// key.clear();
+/// write!(key, "user/{}", 3).unwrap();
+/// assert_eq!(&*key, b"user/3");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct KeyBuf(Vec<u8>);
+
+impl KeyBuf {
+    /// Creates an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty buffer with room for keys of `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    /// Empties the buffer, keeping its allocation.
+    pub fn clear(&mut self) -> &mut Self {
+        self.0.clear();
+        self
+    }
+
+    /// Appends a byte.
+    pub fn push(&mut self, byte: u8) -> &mut Self {
+        self.0.push(byte);
+        self
+    }
+
+    /// Appends bytes.
+    pub fn extend(&mut self, bytes: impl AsRef<[u8]>) -> &mut Self {
+        self.0.extend_from_slice(bytes.as_ref());
+        self
+    }
+}
+
+impl ops::Deref for KeyBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for KeyBuf {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Appends to the buffer, which never fails.
+impl io::Write for KeyBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A value of any [bytemuck::Pod] type, stored as its in-memory representation. Requires the
+/// `bytemuck` feature.
+///
+/// Decoding fails with [Error::DecodeError] unless the data is exactly the size of `T`, and
+/// copies it into an owned `T` rather than borrowing it from the memory map, as data there isn't
+/// necessarily aligned for `T`. As with [Pod], the bytes are in native byte order.
+#[cfg(feature = "bytemuck")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Deref, DerefMut)]
+pub struct PodValue<T>(pub T);
+
+#[cfg(feature = "bytemuck")]
+impl<'tx, T: bytemuck::Pod> TableObject<'tx> for PodValue<T> {
     fn decode(data_val: &[u8]) -> Result<Self, Error>
     where
         Self: Sized,
     {
-        #[derive(Clone, Debug, Display, Error)]
-        struct InvalidSize<const LEN: usize> {
-            got: usize,
+        if data_val.len() != mem::size_of::<T>() {
+            return Err(Error::DecodeError(Box::new(InvalidSize {
+                expected: mem::size_of::<T>(),
+                got: data_val.len(),
+            })));
         }
+        Ok(Self(bytemuck::pod_read_unaligned(data_val)))
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> AsRef<[u8]> for PodValue<T> {
+    fn as_ref(&self) -> &[u8] {
+        bytemuck::bytes_of(&self.0)
+    }
+}
 
+#[derive(Clone, Debug, Display, Error)]
+#[display(fmt = "expected {} bytes, got {}", expected, got)]
+struct InvalidSize {
+    expected: usize,
+    got: usize,
+}
+
+/// Fixed-size values of any width, such as hashes. Decoding fails with [Error::DecodeError] unless
+/// the data is exactly `LEN` bytes long.
+impl<'tx, const LEN: usize> TableObject<'tx> for [u8; LEN] {
+    fn decode(data_val: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
         if data_val.len() != LEN {
-            return Err(Error::DecodeError(Box::new(InvalidSize::<LEN> {
+            return Err(Error::DecodeError(Box::new(InvalidSize {
+                expected: LEN,
                 got: data_val.len(),
             })));
         }
@@ -115,3 +278,45 @@ impl<'tx, const LEN: usize> TableObject<'tx> for [u8; LEN] {
         Ok(a)
     }
 }
+
+/// Plain old data: types that can be stored as their in-memory representation.
+///
+/// Values are read back with [Pod::from_bytes()], which checks the length and doesn't require the
+/// data to be aligned. The bytes are in native byte order, so a database of such values is not
+/// portable between platforms of different endianness.
+///
+/// With the `derive` feature, `#[derive(TableObject)]` implements this trait, along with
+/// [TableObject] and `AsRef<[u8]>`, for `#[repr(C)]` structs whose fields are all [Pod].
+///
+/// # Safety
+///
+/// Implementors must have no padding bytes, must not contain pointers or references, and every
+/// bit pattern of the right size must be a valid value.
+pub unsafe trait Pod: Sized {
+    /// Returns the in-memory representation of the value.
+    fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self as *const Self as *const u8, mem::size_of::<Self>()) }
+    }
+
+    /// Reads a value from its in-memory representation, failing with [Error::DecodeError] if
+    /// `data` is not exactly the size of `Self`.
+    fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != mem::size_of::<Self>() {
+            return Err(Error::DecodeError(Box::new(InvalidSize {
+                expected: mem::size_of::<Self>(),
+                got: data.len(),
+            })));
+        }
+        Ok(unsafe { ptr::read_unaligned(data.as_ptr() as *const Self) })
+    }
+}
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(unsafe impl Pod for $t {})*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
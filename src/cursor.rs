@@ -3,7 +3,10 @@ use crate::{
     error::{mdbx_result, Error, Result},
     flags::*,
     mdbx_try_optional,
-    transaction::{txn_execute, TransactionKind, RW},
+    transaction::{
+        check_integer_sizes, check_integer_sizes_for, check_reservable, dbi_flags, txn_execute,
+        TransactionKind, RW,
+    },
     EnvironmentKind, TableObject, Transaction,
 };
 use ffi::{
@@ -14,7 +17,15 @@ use ffi::{
 };
 use libc::{c_uint, c_void};
 use parking_lot::Mutex;
-use std::{borrow::Cow, fmt, marker::PhantomData, mem, ptr, result, sync::Arc};
+use std::{
+    borrow::Cow,
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    ptr, result, slice,
+    sync::Arc,
+};
 
 /// A cursor for navigating the items within a database.
 pub struct Cursor<'txn, K>
@@ -153,6 +164,9 @@ where
     }
 
     /// [DatabaseFlags::DUP_SORT]-only: Position at first data item of current key.
+    ///
+    /// This is the smallest data item of the key. Returns [Error::InvalidValue] if the cursor is
+    /// not positioned on a key.
     pub fn first_dup<Value>(&mut self) -> Result<Option<Value>>
     where
         Value: TableObject<'txn>,
@@ -168,6 +182,30 @@ where
         self.get_value(Some(k), Some(v), MDBX_GET_BOTH)
     }
 
+    /// [DatabaseFlags::DUP_SORT]-only: Check whether the given key/data pair exists, without
+    /// decoding anything. If it does, the cursor is positioned at the pair afterwards;
+    /// otherwise its position is unspecified.
+    pub fn contains_dup(&mut self, k: &[u8], v: &[u8]) -> Result<bool> {
+        unsafe {
+            let mut key_val = slice_to_val(Some(k));
+            let mut data_val = slice_to_val(Some(v));
+            txn_execute(&*self.txn, |_| {
+                match ffi::mdbx_cursor_get(self.cursor, &mut key_val, &mut data_val, MDBX_GET_BOTH)
+                {
+                    // `MDBX_GET_BOTH` is documented to match exactly, but for keys with several
+                    // data items libmdbx 0.11 searches them like `MDBX_GET_BOTH_RANGE` does and
+                    // succeeds on the first one greater than or equal to `v`.
+                    ffi::MDBX_SUCCESS => Ok(slice::from_raw_parts(
+                        data_val.iov_base as *const u8,
+                        data_val.iov_len,
+                    ) == v),
+                    ffi::MDBX_NOTFOUND => Ok(false),
+                    err_code => Err(Error::from_err_code(err_code)),
+                }
+            })
+        }
+    }
+
     /// [DatabaseFlags::DUP_SORT]-only: Position at given key and at first data greater than or equal to specified data.
     pub fn get_both_range<Value>(&mut self, k: &[u8], v: &[u8]) -> Result<Option<Value>>
     where
@@ -203,7 +241,10 @@ where
         self.get_full(None, None, MDBX_LAST)
     }
 
-    /// DupSort-only: Position at last data item of current key.
+    /// [DatabaseFlags::DUP_SORT]-only: Position at last data item of current key.
+    ///
+    /// This is the largest data item of the key. Returns [Error::InvalidValue] if the cursor is
+    /// not positioned on a key.
     pub fn last_dup<Value>(&mut self) -> Result<Option<Value>>
     where
         Value: TableObject<'txn>,
@@ -326,10 +367,63 @@ where
         Ok(Some((found, k.unwrap(), v)))
     }
 
+    /// Counts the items after the cursor's position, i.e. the number of items [Self::iter()]
+    /// would return, without moving the cursor.
+    ///
+    /// The count is exact, but this steps through every remaining item, so it takes time
+    /// proportional to their number. The number of items in the whole database is available in
+    /// constant time from [Transaction::db_stat()].
+    pub fn count_remaining(&self) -> Result<usize> {
+        let mut cursor = txn_execute(&*self.txn, |_| Self::new_at_position(self))?;
+        let mut count = 0;
+        while cursor.next::<(), ()>()?.is_some() {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Returns the number of data items of the key at the cursor's position, which is always 1
+    /// for databases without [DatabaseFlags::DUP_SORT].
+    pub fn dup_count(&self) -> Result<usize> {
+        let mut count = 0;
+        mdbx_result(txn_execute(&*self.txn, |_| unsafe {
+            ffi::mdbx_cursor_count(self.cursor, &mut count)
+        }))?;
+        Ok(count)
+    }
+
+    /// Returns whether the cursor is positioned at an item, so that e.g. [Self::get_current()] and
+    /// [Cursor::del()] apply to it.
+    ///
+    /// New cursors aren't positioned, and neither are cursors whose last seek failed. Stepping past
+    /// either end of the database leaves the cursor at the first or last item.
+    pub fn is_positioned(&self) -> Result<bool> {
+        unsafe {
+            let mut key_val = slice_to_val(None);
+            let mut data_val = slice_to_val(None);
+            txn_execute(&*self.txn, |_| {
+                match ffi::mdbx_cursor_get(
+                    self.cursor,
+                    &mut key_val,
+                    &mut data_val,
+                    MDBX_GET_CURRENT,
+                ) {
+                    ffi::MDBX_SUCCESS => Ok(true),
+                    ffi::MDBX_NOTFOUND | libc::ENODATA => Ok(false),
+                    err_code => Err(Error::from_err_code(err_code)),
+                }
+            })
+        }
+    }
+
     /// Iterate over database items. The iterator will begin with item next
     /// after the cursor, and continue until the end of the database. For new
     /// cursors, the iterator will begin with the first item in the database.
     ///
+    /// The item the cursor is positioned at is not included. Use [Self::iter_start()] to
+    /// iterate from the beginning regardless of the cursor position, or [Self::iter_from()] to
+    /// include the item found by a seek.
+    ///
     /// For databases with duplicate data items ([DatabaseFlags::DUP_SORT]), the
     /// duplicate data items of each key will be returned before moving on to
     /// the next key.
@@ -342,7 +436,36 @@ where
         Iter::new(self, ffi::MDBX_NEXT, ffi::MDBX_NEXT)
     }
 
-    /// Iterate over database items starting from the beginning of the database.
+    /// Iterate over database items as owned byte vectors.
+    ///
+    /// This is [Self::iter()] with both the key and the value decoded as [`Vec<u8>`], for when
+    /// the raw bytes are all that is needed.
+    ///
+    /// ```
+    /// # use mdbx::{Environment, NoWriteMap, WriteFlags};
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let env = Environment::<NoWriteMap>::new().open(dir.path()).unwrap();
+    /// let txn = env.begin_rw_txn().unwrap();
+    /// let db = txn.open_db(None).unwrap();
+    /// txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+    /// txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+    ///
+    /// let mut cursor = txn.cursor(&db).unwrap();
+    /// let items = cursor.iter_bytes().collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(
+    ///     items,
+    ///     vec![
+    ///         (b"key1".to_vec(), b"val1".to_vec()),
+    ///         (b"key2".to_vec(), b"val2".to_vec()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter_bytes(&mut self) -> Iter<'txn, '_, K, Vec<u8>, Vec<u8>> {
+        self.iter()
+    }
+
+    /// Iterate over database items starting from the beginning of the database, regardless of
+    /// the current cursor position.
     ///
     /// For databases with duplicate data items ([DatabaseFlags::DUP_SORT]), the
     /// duplicate data items of each key will be returned before moving on to
@@ -373,6 +496,72 @@ where
         Iter::new(self, ffi::MDBX_GET_CURRENT, ffi::MDBX_NEXT)
     }
 
+    /// Iterate over database items in descending order, starting from the greatest key less than
+    /// or equal to the given key.
+    ///
+    /// If `key` is smaller than all keys in the database the iterator is empty, and if it is
+    /// greater than all of them the iterator starts at the last item. For databases with
+    /// duplicate data items ([DatabaseFlags::DUP_SORT]), the duplicate data items of each key are
+    /// also returned in descending order.
+    pub fn iter_from_rev<Key, Value>(&mut self, key: &[u8]) -> Iter<'txn, '_, K, Key, Value>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        let res = (|| -> Result<Option<((), ())>> {
+            match self.set_range::<Cow<'txn, [u8]>, ()>(key)? {
+                // Step past the duplicates of an exact match, then back onto its last one.
+                Some((found, ())) if *found == *key => match self.next_nodup::<(), ()>()? {
+                    Some(_) => self.prev(),
+                    None => self.last(),
+                },
+                Some(_) => self.prev(),
+                None => self.last(),
+            }
+        })();
+        match res {
+            Ok(Some(_)) => Iter::new(self, ffi::MDBX_GET_CURRENT, ffi::MDBX_PREV),
+            Ok(None) => Iter::Err(None),
+            Err(error) => Iter::Err(Some(error)),
+        }
+    }
+
+    /// Iterate over database items without decoding them.
+    ///
+    /// Like [Self::iter()], but items are handed out as raw byte slices through
+    /// [IterRaw::advance()], each valid only until the next step. This suits databases holding
+    /// values of different types, where the key indicates how to decode the value.
+    pub fn iter_raw(&mut self) -> IterRaw<'txn, '_, K> {
+        IterRaw {
+            cursor: self,
+            op: Some(ffi::MDBX_NEXT),
+            key_buf: Vec::new(),
+            data_buf: Vec::new(),
+        }
+    }
+
+    /// Iterate over database items whose key starts with `prefix`.
+    ///
+    /// The cursor is positioned at the first key greater than or equal to `prefix`, and iteration
+    /// stops at the first key that does not start with it. An empty prefix iterates over the whole
+    /// database.
+    pub fn iter_prefix<Key, Value>(&mut self, prefix: &[u8]) -> IterPrefix<'txn, '_, K, Key, Value>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        let res: Result<Option<((), ())>> = self.set_range(prefix);
+        if let Err(error) = res {
+            return IterPrefix::Err(Some(error));
+        };
+        IterPrefix::Ok {
+            cursor: self,
+            prefix: prefix.to_vec(),
+            op: ffi::MDBX_GET_CURRENT,
+            _marker: PhantomData,
+        }
+    }
+
     /// Iterate over duplicate database items. The iterator will begin with the
     /// item next after the cursor, and continue until the end of the database.
     /// Each item will be returned as an iterator of its duplicates.
@@ -384,6 +573,23 @@ where
         IterDup::new(self, ffi::MDBX_NEXT)
     }
 
+    /// Iterate over database keys, each with all of its values collected into a [Vec].
+    ///
+    /// The iterator begins with the key after the cursor's current key, or with the first key
+    /// for new cursors. In databases without [DatabaseFlags::DUP_SORT] every key has exactly
+    /// one value.
+    pub fn iter_grouped<Key, Value>(&mut self) -> IterGrouped<'txn, '_, K, Key, Value>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        IterGrouped {
+            cursor: self,
+            op: Some(ffi::MDBX_NEXT_NODUP),
+            _marker: PhantomData,
+        }
+    }
+
     /// Iterate over duplicate database items starting from the beginning of the
     /// database. Each item will be returned as an iterator of its duplicates.
     pub fn iter_dup_start<Key, Value>(&mut self) -> IterDup<'txn, '_, K, Key, Value>
@@ -425,12 +631,71 @@ where
         };
         Iter::new(self, ffi::MDBX_GET_CURRENT, ffi::MDBX_NEXT_DUP)
     }
+
+    /// Iterate over all values of the given key, in order.
+    ///
+    /// Unlike [Self::iter_dup_of()], the seek to `key` happens up front and its error, if any,
+    /// is returned directly. If `key` is not in the database the iterator is empty. The first
+    /// value is included, even though the seek has already read it.
+    pub fn values_of<Value>(&mut self, key: &[u8]) -> Result<IterValues<'txn, '_, K, Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        Ok(IterValues(match self.set::<()>(key)? {
+            Some(()) => Iter::new(self, ffi::MDBX_GET_CURRENT, ffi::MDBX_NEXT_DUP),
+            None => Iter::Err(None),
+        }))
+    }
+
+    /// Iterate over the values of the given key, starting at the first value greater than or
+    /// equal to `start_value`.
+    ///
+    /// This can be used to resume a paginated scan of a key's values: pass the last value seen,
+    /// and skip it if it is returned again. As with [Self::values_of()], the seek happens up front,
+    /// and if there are no such values the iterator is empty.
+    pub fn values_from<Value>(
+        &mut self,
+        key: &[u8],
+        start_value: &[u8],
+    ) -> Result<IterValues<'txn, '_, K, Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        Ok(IterValues(
+            match self.get_both_range::<()>(key, start_value)? {
+                Some(()) => Iter::new(self, ffi::MDBX_GET_CURRENT, ffi::MDBX_NEXT_DUP),
+                None => Iter::Err(None),
+            },
+        ))
+    }
 }
 
 impl<'txn> Cursor<'txn, RW> {
     /// Puts a key/data pair into the database. The cursor will be positioned at
     /// the new data item, or on failure usually near it.
     pub fn put(&mut self, key: &[u8], data: &[u8], flags: WriteFlags) -> Result<()> {
+        self.put_inner(key, data, flags, None)
+    }
+
+    /// Like [Cursor::put()], with the flags of the database already looked up, for writing many
+    /// items.
+    pub(crate) fn put_with_db_flags(
+        &mut self,
+        key: &[u8],
+        data: &[u8],
+        flags: WriteFlags,
+        db_flags: DatabaseFlags,
+    ) -> Result<()> {
+        self.put_inner(key, data, flags, Some(db_flags))
+    }
+
+    fn put_inner(
+        &mut self,
+        key: &[u8],
+        data: &[u8],
+        flags: WriteFlags,
+        db_flags: Option<DatabaseFlags>,
+    ) -> Result<()> {
         let key_val: ffi::MDBX_val = ffi::MDBX_val {
             iov_len: key.len(),
             iov_base: key.as_ptr() as *mut c_void,
@@ -439,21 +704,73 @@ impl<'txn> Cursor<'txn, RW> {
             iov_len: data.len(),
             iov_base: data.as_ptr() as *mut c_void,
         };
-        mdbx_result(unsafe {
-            txn_execute(&*self.txn, |_| {
-                ffi::mdbx_cursor_put(self.cursor, &key_val, &mut data_val, flags.bits())
-            })
-        })?;
+        unsafe {
+            txn_execute(&*self.txn, |txn| {
+                match db_flags {
+                    Some(db_flags) => check_integer_sizes_for(db_flags, key.len(), data.len())?,
+                    None => check_integer_sizes(
+                        txn,
+                        ffi::mdbx_cursor_dbi(self.cursor),
+                        key.len(),
+                        data.len(),
+                    )?,
+                }
+                mdbx_result(ffi::mdbx_cursor_put(
+                    self.cursor,
+                    &key_val,
+                    &mut data_val,
+                    flags.bits(),
+                ))
+            })?;
+        }
 
         Ok(())
     }
 
+    /// Puts an item with a value of length `len` at the given key, and returns a buffer for the
+    /// caller to write the value into. The buffer is not initialized and must be completely
+    /// filled. The cursor will be positioned at the new item.
+    ///
+    /// With [WriteFlags::CURRENT] this replaces the value at the current position, whose key must
+    /// match `key`, with a buffer of the new length.
+    ///
+    /// Reserving is not supported in [DatabaseFlags::DUP_SORT] databases, where this returns
+    /// [Error::Incompatible].
+    pub fn reserve(&mut self, key: &[u8], len: usize, flags: WriteFlags) -> Result<&mut [u8]> {
+        let key_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: key.len(),
+            iov_base: key.as_ptr() as *mut c_void,
+        };
+        let mut data_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: len,
+            iov_base: ptr::null_mut(),
+        };
+        unsafe {
+            txn_execute(&*self.txn, |txn| {
+                let db_flags = dbi_flags(txn, ffi::mdbx_cursor_dbi(self.cursor))?;
+                check_reservable(db_flags)?;
+                check_integer_sizes_for(db_flags, key.len(), len)?;
+                mdbx_result(ffi::mdbx_cursor_put(
+                    self.cursor,
+                    &key_val,
+                    &mut data_val,
+                    flags.bits() | ffi::MDBX_RESERVE,
+                ))
+            })?;
+            Ok(slice::from_raw_parts_mut(
+                data_val.iov_base as *mut u8,
+                data_val.iov_len,
+            ))
+        }
+    }
+
     /// Deletes the current key/data pair.
     ///
     /// ### Flags
     ///
-    /// [WriteFlags::NO_DUP_DATA] may be used to delete all data items for the
-    /// current key, if the database was opened with [DatabaseFlags::DUP_SORT].
+    /// [WriteFlags::ALLDUPS] (or [WriteFlags::NO_DUP_DATA], which MDBX accepts for
+    /// compatibility) may be used to delete all data items for the current key, if the database
+    /// was opened with [DatabaseFlags::DUP_SORT]. For other databases the flag has no effect.
     pub fn del(&mut self, flags: WriteFlags) -> Result<()> {
         mdbx_result(unsafe {
             txn_execute(&*self.txn, |_| {
@@ -463,6 +780,19 @@ impl<'txn> Cursor<'txn, RW> {
 
         Ok(())
     }
+
+    /// [DatabaseFlags::DUP_SORT]-only: Deletes the given key/data pair, leaving the other data
+    /// items of the key in place.
+    ///
+    /// Returns whether the pair was found and deleted. Either way the cursor is left positioned
+    /// near it.
+    pub fn del_dup(&mut self, key: &[u8], data: &[u8]) -> Result<bool> {
+        if !self.contains_dup(key, data)? {
+            return Ok(false);
+        }
+        self.del(WriteFlags::empty())?;
+        Ok(true)
+    }
 }
 
 impl<'txn, K> Clone for Cursor<'txn, K>
@@ -494,6 +824,56 @@ where
     }
 }
 
+/// A [Cursor] on a database known to have been created with [DatabaseFlags::DUP_SORT], returned
+/// by [Transaction::cursor_dup()].
+///
+/// It dereferences to the [Cursor], so all of its methods are available; the `DUP_SORT`-only ones
+/// such as [Cursor::next_dup()] and [Cursor::get_both()] are guaranteed to apply.
+#[derive(Debug)]
+pub struct DupCursor<'txn, K>(Cursor<'txn, K>)
+where
+    K: TransactionKind;
+
+impl<'txn, K> DupCursor<'txn, K>
+where
+    K: TransactionKind,
+{
+    pub(crate) fn new<E: EnvironmentKind>(
+        txn: &'txn Transaction<K, E>,
+        db: &Database<'txn>,
+    ) -> Result<Self> {
+        if !txn.db_flags(db)?.contains(DatabaseFlags::DUP_SORT) {
+            return Err(Error::Incompatible);
+        }
+        Ok(Self(Cursor::new(txn, db)?))
+    }
+
+    /// Returns the underlying [Cursor].
+    pub fn into_inner(self) -> Cursor<'txn, K> {
+        self.0
+    }
+}
+
+impl<'txn, K> Deref for DupCursor<'txn, K>
+where
+    K: TransactionKind,
+{
+    type Target = Cursor<'txn, K>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'txn, K> DerefMut for DupCursor<'txn, K>
+where
+    K: TransactionKind,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 unsafe fn slice_to_val(slice: Option<&[u8]>) -> ffi::MDBX_val {
     match slice {
         Some(slice) => ffi::MDBX_val {
@@ -562,7 +942,11 @@ where
     Value: TableObject<'txn>,
 {
     /// Creates a new iterator backed by the given cursor.
-    fn new(cursor: Cursor<'txn, K>, op: ffi::MDBX_cursor_op, next_op: ffi::MDBX_cursor_op) -> Self {
+    pub(crate) fn new(
+        cursor: Cursor<'txn, K>,
+        op: ffi::MDBX_cursor_op,
+        next_op: ffi::MDBX_cursor_op,
+    ) -> Self {
         IntoIter::Ok {
             cursor,
             op,
@@ -730,116 +1114,352 @@ where
     }
 }
 
-/// An iterator over the keys and duplicate values in an MDBX database.
-///
-/// The yielded items of the iterator are themselves iterators over the duplicate values for a
-/// specific key.
-pub enum IterDup<'txn, 'cur, K, Key, Value>
+/// An iterator over the key/data pairs in an MDBX database whose key starts with a given prefix.
+pub enum IterPrefix<'txn, 'cur, K, Key, Value>
 where
     K: TransactionKind,
     Key: TableObject<'txn>,
     Value: TableObject<'txn>,
 {
-    /// An iterator that returns an error on every call to Iter.next().
-    /// Cursor.iter*() creates an Iter of this type when MDBX returns an error
-    /// on retrieval of a cursor.  Using this variant instead of returning
-    /// an error makes Cursor.iter()* methods infallible, so consumers only
-    /// need to check the result of Iter.next().
+    /// An iterator that returns an error on the first call to [IterPrefix::next()], and
+    /// [None] afterwards. Also used once iteration has moved past the prefix.
     Err(Option<Error>),
 
-    /// An iterator that returns an Item on calls to Iter.next().
-    /// The Item is a Result<(&'txn [u8], &'txn [u8])>, so this variant
-    /// might still return an error, if retrieval of the key/value pair
-    /// fails for some reason.
+    /// An iterator that returns an Item on calls to [IterPrefix::next()].
     Ok {
         /// The MDBX cursor with which to iterate.
         cursor: &'cur mut Cursor<'txn, K>,
 
-        /// The first operation to perform when the consumer calls Iter.next().
-        op: c_uint,
+        /// The prefix all returned keys start with.
+        prefix: Vec<u8>,
+
+        /// The next operation to perform when the consumer calls [IterPrefix::next()].
+        op: ffi::MDBX_cursor_op,
 
         _marker: PhantomData<fn(&'txn (Key, Value))>,
     },
 }
 
-impl<'txn, 'cur, K, Key, Value> IterDup<'txn, 'cur, K, Key, Value>
+impl<'txn, 'cur, K, Key, Value> Iterator for IterPrefix<'txn, 'cur, K, Key, Value>
 where
     K: TransactionKind,
     Key: TableObject<'txn>,
     Value: TableObject<'txn>,
 {
-    /// Creates a new iterator backed by the given cursor.
-    fn new(cursor: &'cur mut Cursor<'txn, K>, op: c_uint) -> Self {
-        IterDup::Ok {
-            cursor,
-            op,
-            _marker: PhantomData,
+    type Item = Result<(Key, Value)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = match self {
+            IterPrefix::Ok {
+                cursor, prefix, op, ..
+            } => {
+                let mut key = ffi::MDBX_val {
+                    iov_len: 0,
+                    iov_base: ptr::null_mut(),
+                };
+                let mut data = ffi::MDBX_val {
+                    iov_len: 0,
+                    iov_base: ptr::null_mut(),
+                };
+                let op = mem::replace(op, ffi::MDBX_NEXT);
+                unsafe {
+                    txn_execute(&*cursor.txn, |txn| {
+                        match ffi::mdbx_cursor_get(cursor.cursor(), &mut key, &mut data, op) {
+                            ffi::MDBX_SUCCESS => {
+                                let raw_key =
+                                    slice::from_raw_parts(key.iov_base as *const u8, key.iov_len);
+                                if !raw_key.starts_with(prefix) {
+                                    return None;
+                                }
+                                Some(
+                                    Key::decode_val::<K>(txn, &key).and_then(|key| {
+                                        Ok((key, Value::decode_val::<K>(txn, &data)?))
+                                    }),
+                                )
+                            }
+                            // EINVAL can occur when the prefix is greater than all keys in the
+                            // database, see Iter::next().
+                            ffi::MDBX_NOTFOUND | libc::ENODATA => None,
+                            error => Some(Err(Error::from_err_code(error))),
+                        }
+                    })
+                }
+            }
+            IterPrefix::Err(err) => return err.take().map(Err),
+        };
+        if item.is_none() {
+            *self = IterPrefix::Err(None);
         }
+        item
     }
 }
 
-impl<'txn, 'cur, K, Key, Value> fmt::Debug for IterDup<'txn, 'cur, K, Key, Value>
+/// An iterator over the keys of an MDBX database and all of their values, created by
+/// [Cursor::iter_grouped()].
+#[derive(Debug)]
+pub struct IterGrouped<'txn, 'cur, K, Key, Value>
 where
     K: TransactionKind,
     Key: TableObject<'txn>,
     Value: TableObject<'txn>,
 {
-    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
-        f.debug_struct("IterDup").finish()
+    cursor: &'cur mut Cursor<'txn, K>,
+    /// The operation moving to the next key, or [None] once iteration has finished.
+    op: Option<ffi::MDBX_cursor_op>,
+    _marker: PhantomData<fn(&'txn (Key, Value))>,
+}
+
+impl<'txn, 'cur, K, Key, Value> IterGrouped<'txn, 'cur, K, Key, Value>
+where
+    K: TransactionKind,
+    Key: TableObject<'txn>,
+    Value: TableObject<'txn>,
+{
+    fn next_group(&mut self, op: ffi::MDBX_cursor_op) -> Result<Option<(Key, Vec<Value>)>> {
+        let (key, value) = match self.cursor.get_full(None, None, op)? {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        // Without DUP_SORT, MDBX_NEXT_DUP would move on to the next key, but the count is 1.
+        let mut count = 0;
+        mdbx_result(txn_execute(&*self.cursor.txn, |_| unsafe {
+            ffi::mdbx_cursor_count(self.cursor.cursor(), &mut count)
+        }))?;
+        let mut values = Vec::with_capacity(count);
+        values.push(value);
+        for _ in 1..count {
+            match self.cursor.get_value(None, None, MDBX_NEXT_DUP)? {
+                Some(value) => values.push(value),
+                None => break,
+            }
+        }
+        Ok(Some((key, values)))
     }
 }
 
-impl<'txn, 'cur, K, Key, Value> Iterator for IterDup<'txn, 'cur, K, Key, Value>
+impl<'txn, 'cur, K, Key, Value> Iterator for IterGrouped<'txn, 'cur, K, Key, Value>
 where
     K: TransactionKind,
     Key: TableObject<'txn>,
     Value: TableObject<'txn>,
 {
-    type Item = IntoIter<'txn, K, Key, Value>;
+    type Item = Result<(Key, Vec<Value>)>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            IterDup::Ok { cursor, op, .. } => {
-                let mut key = ffi::MDBX_val {
-                    iov_len: 0,
-                    iov_base: ptr::null_mut(),
-                };
-                let mut data = ffi::MDBX_val {
-                    iov_len: 0,
-                    iov_base: ptr::null_mut(),
-                };
-                let op = mem::replace(op, ffi::MDBX_NEXT_NODUP);
-
-                txn_execute(&*cursor.txn, |_| {
-                    let err_code =
-                        unsafe { ffi::mdbx_cursor_get(cursor.cursor(), &mut key, &mut data, op) };
-
-                    if err_code == ffi::MDBX_SUCCESS {
-                        Some(IntoIter::new(
-                            Cursor::new_at_position(&**cursor).unwrap(),
-                            ffi::MDBX_GET_CURRENT,
-                            ffi::MDBX_NEXT_DUP,
-                        ))
-                    } else {
-                        None
-                    }
-                })
-            }
-            IterDup::Err(err) => err.take().map(|e| IntoIter::Err(Some(e))),
+        let op = self.op?;
+        let item = self.next_group(op).transpose();
+        if !matches!(item, Some(Ok(_))) {
+            self.op = None;
         }
+        item
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::environment::*;
-    use tempfile::tempdir;
-
-    type Environment = crate::Environment<NoWriteMap>;
+/// An iterator over the values of a single key, created by [Cursor::values_of()].
+#[derive(Debug)]
+pub struct IterValues<'txn, 'cur, K, Value>(Iter<'txn, 'cur, K, (), Value>)
+where
+    K: TransactionKind,
+    Value: TableObject<'txn>;
 
-    #[test]
-    fn test_get() {
+impl<'txn, 'cur, K, Value> Iterator for IterValues<'txn, 'cur, K, Value>
+where
+    K: TransactionKind,
+    Value: TableObject<'txn>,
+{
+    type Item = Result<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|res| res.map(|(_, value)| value))
+    }
+}
+
+/// A lending iterator over the raw key/value pairs in an MDBX database, created by
+/// [Cursor::iter_raw()].
+///
+/// Each item borrows the iterator and is only valid until the next call to
+/// [IterRaw::advance()], which lets the caller decode every pair as whatever type it needs
+/// without copying. Data on pages modified by a read-write transaction is copied into a buffer
+/// owned by the iterator, as later writes may change those pages.
+#[derive(Debug)]
+pub struct IterRaw<'txn, 'cur, K>
+where
+    K: TransactionKind,
+{
+    cursor: &'cur mut Cursor<'txn, K>,
+    op: Option<ffi::MDBX_cursor_op>,
+    key_buf: Vec<u8>,
+    data_buf: Vec<u8>,
+}
+
+impl<'txn, 'cur, K> IterRaw<'txn, 'cur, K>
+where
+    K: TransactionKind,
+{
+    /// Moves to the next key/value pair and returns it, or [None] once the end of the database
+    /// has been reached.
+    pub fn advance(&mut self) -> Option<Result<(&[u8], &[u8])>> {
+        let op = self.op.replace(ffi::MDBX_NEXT)?;
+        let mut key = ffi::MDBX_val {
+            iov_len: 0,
+            iov_base: ptr::null_mut(),
+        };
+        let mut data = ffi::MDBX_val {
+            iov_len: 0,
+            iov_base: ptr::null_mut(),
+        };
+        let res = unsafe {
+            txn_execute(&*self.cursor.txn, |txn| {
+                match ffi::mdbx_cursor_get(self.cursor.cursor(), &mut key, &mut data, op) {
+                    ffi::MDBX_SUCCESS if K::ONLY_CLEAN => Ok(Some((false, false))),
+                    ffi::MDBX_SUCCESS => Ok(Some((
+                        mdbx_result(ffi::mdbx_is_dirty(txn, key.iov_base))?,
+                        mdbx_result(ffi::mdbx_is_dirty(txn, data.iov_base))?,
+                    ))),
+                    ffi::MDBX_NOTFOUND | libc::ENODATA => Ok(None),
+                    error => Err(Error::from_err_code(error)),
+                }
+            })
+        };
+        let (key_dirty, data_dirty) = match res {
+            Ok(Some(dirty)) => dirty,
+            Ok(None) => {
+                self.op = None;
+                return None;
+            }
+            Err(e) => return Some(Err(e)),
+        };
+
+        let key = unsafe { slice::from_raw_parts(key.iov_base as *const u8, key.iov_len) };
+        let data = unsafe { slice::from_raw_parts(data.iov_base as *const u8, data.iov_len) };
+        let key = if key_dirty {
+            self.key_buf.clear();
+            self.key_buf.extend_from_slice(key);
+            &self.key_buf[..]
+        } else {
+            key
+        };
+        let data = if data_dirty {
+            self.data_buf.clear();
+            self.data_buf.extend_from_slice(data);
+            &self.data_buf[..]
+        } else {
+            data
+        };
+        Some(Ok((key, data)))
+    }
+}
+
+/// An iterator over the keys and duplicate values in an MDBX database.
+///
+/// The yielded items of the iterator are themselves iterators over the duplicate values for a
+/// specific key.
+pub enum IterDup<'txn, 'cur, K, Key, Value>
+where
+    K: TransactionKind,
+    Key: TableObject<'txn>,
+    Value: TableObject<'txn>,
+{
+    /// An iterator that returns an error on every call to Iter.next().
+    /// Cursor.iter*() creates an Iter of this type when MDBX returns an error
+    /// on retrieval of a cursor.  Using this variant instead of returning
+    /// an error makes Cursor.iter()* methods infallible, so consumers only
+    /// need to check the result of Iter.next().
+    Err(Option<Error>),
+
+    /// An iterator that returns an Item on calls to Iter.next().
+    /// The Item is a Result<(&'txn [u8], &'txn [u8])>, so this variant
+    /// might still return an error, if retrieval of the key/value pair
+    /// fails for some reason.
+    Ok {
+        /// The MDBX cursor with which to iterate.
+        cursor: &'cur mut Cursor<'txn, K>,
+
+        /// The first operation to perform when the consumer calls Iter.next().
+        op: c_uint,
+
+        _marker: PhantomData<fn(&'txn (Key, Value))>,
+    },
+}
+
+impl<'txn, 'cur, K, Key, Value> IterDup<'txn, 'cur, K, Key, Value>
+where
+    K: TransactionKind,
+    Key: TableObject<'txn>,
+    Value: TableObject<'txn>,
+{
+    /// Creates a new iterator backed by the given cursor.
+    fn new(cursor: &'cur mut Cursor<'txn, K>, op: c_uint) -> Self {
+        IterDup::Ok {
+            cursor,
+            op,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'txn, 'cur, K, Key, Value> fmt::Debug for IterDup<'txn, 'cur, K, Key, Value>
+where
+    K: TransactionKind,
+    Key: TableObject<'txn>,
+    Value: TableObject<'txn>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        f.debug_struct("IterDup").finish()
+    }
+}
+
+impl<'txn, 'cur, K, Key, Value> Iterator for IterDup<'txn, 'cur, K, Key, Value>
+where
+    K: TransactionKind,
+    Key: TableObject<'txn>,
+    Value: TableObject<'txn>,
+{
+    type Item = IntoIter<'txn, K, Key, Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IterDup::Ok { cursor, op, .. } => {
+                let mut key = ffi::MDBX_val {
+                    iov_len: 0,
+                    iov_base: ptr::null_mut(),
+                };
+                let mut data = ffi::MDBX_val {
+                    iov_len: 0,
+                    iov_base: ptr::null_mut(),
+                };
+                let op = mem::replace(op, ffi::MDBX_NEXT_NODUP);
+
+                txn_execute(&*cursor.txn, |_| {
+                    let err_code =
+                        unsafe { ffi::mdbx_cursor_get(cursor.cursor(), &mut key, &mut data, op) };
+
+                    if err_code == ffi::MDBX_SUCCESS {
+                        Some(IntoIter::new(
+                            Cursor::new_at_position(&**cursor).unwrap(),
+                            ffi::MDBX_GET_CURRENT,
+                            ffi::MDBX_NEXT_DUP,
+                        ))
+                    } else {
+                        None
+                    }
+                })
+            }
+            IterDup::Err(err) => err.take().map(|e| IntoIter::Err(Some(e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::environment::*;
+    use tempfile::tempdir;
+
+    type Environment = crate::Environment<NoWriteMap>;
+
+    #[test]
+    fn test_get() {
         let dir = tempdir().unwrap();
         let env = Environment::new().open(dir.path()).unwrap();
 
@@ -904,6 +1524,51 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_first_last_dup() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+        txn.put(&db, b"key1", b"val2", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key1", b"val3", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key2", b"val0", WriteFlags::empty()).unwrap();
+
+        let mut cursor = txn.cursor(&db).unwrap();
+        assert!(matches!(cursor.first_dup::<()>(), Err(Error::InvalidValue)));
+        assert!(matches!(cursor.last_dup::<()>(), Err(Error::InvalidValue)));
+
+        assert_eq!(cursor.set(b"key1").unwrap(), Some(*b"val1"));
+        assert_eq!(cursor.last_dup().unwrap(), Some(*b"val3"));
+        assert_eq!(cursor.first_dup().unwrap(), Some(*b"val1"));
+        assert_eq!(cursor.last_dup().unwrap(), Some(*b"val3"));
+        assert_eq!(cursor.get_current().unwrap(), Some((*b"key1", *b"val3")));
+    }
+
+    #[test]
+    fn test_contains_dup() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key1", b"val2", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key2", b"", WriteFlags::empty()).unwrap();
+
+        let mut cursor = txn.cursor(&db).unwrap();
+        assert!(cursor.contains_dup(b"key1", b"val1").unwrap());
+        assert!(cursor.contains_dup(b"key1", b"val2").unwrap());
+        assert_eq!(cursor.get_current().unwrap(), Some((*b"key1", *b"val2")));
+        assert!(!cursor.contains_dup(b"key1", b"val3").unwrap());
+        assert!(!cursor.contains_dup(b"key1", b"val0").unwrap());
+        assert!(!cursor.contains_dup(b"key3", b"val1").unwrap());
+        assert!(cursor.contains_dup(b"key2", b"").unwrap());
+        assert!(!cursor.contains_dup(b"key1", b"").unwrap());
+    }
+
     #[test]
     fn test_get_dupfixed() {
         let dir = tempdir().unwrap();
@@ -996,6 +1661,343 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_iter_from_rev() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn
+            .create_db(Some("plain"), DatabaseFlags::empty())
+            .unwrap();
+        for key in [b"key1", b"key2", b"key3", b"key5"] {
+            txn.put(&db, key, b"val", WriteFlags::empty()).unwrap();
+        }
+        let dups = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        for (key, data) in [(b"key1", b"val1"), (b"key2", b"val1"), (b"key2", b"val2")] {
+            txn.put(&dups, key, data, WriteFlags::empty()).unwrap();
+        }
+
+        let mut cursor = txn.cursor(&db).unwrap();
+        let keys = |iter: Iter<'_, '_, RW, [u8; 4], ()>| {
+            iter.map(|res| res.map(|(key, ())| key))
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+        };
+        assert_eq!(
+            keys(cursor.iter_from_rev(b"key3")),
+            vec![*b"key3", *b"key2", *b"key1"]
+        );
+        assert_eq!(
+            keys(cursor.iter_from_rev(b"key4")),
+            vec![*b"key3", *b"key2", *b"key1"]
+        );
+        assert_eq!(
+            keys(cursor.iter_from_rev(b"key9")),
+            vec![*b"key5", *b"key3", *b"key2", *b"key1"]
+        );
+        assert_eq!(keys(cursor.iter_from_rev(b"key0")), Vec::<[u8; 4]>::new());
+
+        let mut cursor = txn.cursor(&dups).unwrap();
+        assert_eq!(
+            cursor
+                .iter_from_rev(b"key1")
+                .collect::<Result<Vec<_>>>()
+                .unwrap(),
+            vec![(*b"key1", *b"val1")]
+        );
+        assert_eq!(
+            cursor
+                .iter_from_rev(b"key2")
+                .collect::<Result<Vec<_>>>()
+                .unwrap(),
+            vec![
+                (*b"key2", *b"val2"),
+                (*b"key2", *b"val1"),
+                (*b"key1", *b"val1")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_count_remaining() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+        for i in 0..10u8 {
+            txn.put(&db, &[i], b"a", WriteFlags::empty()).unwrap();
+        }
+        txn.put(&db, &[7], b"b", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let mut cursor = txn.cursor(&db).unwrap();
+        assert_eq!(cursor.count_remaining().unwrap(), 11);
+
+        cursor.set::<()>(&[5]).unwrap().unwrap();
+        // Keys 6 to 9, with two values for key 7.
+        assert_eq!(cursor.count_remaining().unwrap(), 5);
+        // The cursor itself hasn't moved.
+        assert_eq!(cursor.get_current().unwrap(), Some((*b"\x05", *b"a")));
+        assert_eq!(cursor.iter::<(), ()>().count(), 5);
+
+        cursor.last::<(), ()>().unwrap().unwrap();
+        assert_eq!(cursor.count_remaining().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_iter_grouped() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let dups = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        let plain = txn
+            .create_db(Some("plain"), DatabaseFlags::empty())
+            .unwrap();
+        for (key, value) in [
+            (b"a", b"1"),
+            (b"b", b"1"),
+            (b"b", b"2"),
+            (b"b", b"3"),
+            (b"c", b"1"),
+        ] {
+            txn.put(&dups, key, value, WriteFlags::empty()).unwrap();
+            txn.put(&plain, key, value, WriteFlags::empty()).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let dups = txn.open_db(Some("dups")).unwrap();
+        let mut cursor = txn.cursor(&dups).unwrap();
+        assert_eq!(
+            cursor
+                .iter_grouped::<[u8; 1], [u8; 1]>()
+                .collect::<Result<Vec<_>>>()
+                .unwrap(),
+            vec![
+                (*b"a", vec![*b"1"]),
+                (*b"b", vec![*b"1", *b"2", *b"3"]),
+                (*b"c", vec![*b"1"]),
+            ]
+        );
+
+        // Starting mid-way through a key's values moves on to the next key.
+        cursor.get_both::<()>(b"b", b"2").unwrap().unwrap();
+        assert_eq!(
+            cursor
+                .iter_grouped::<[u8; 1], [u8; 1]>()
+                .collect::<Result<Vec<_>>>()
+                .unwrap(),
+            vec![(*b"c", vec![*b"1"])]
+        );
+
+        let plain = txn.open_db(Some("plain")).unwrap();
+        let mut cursor = txn.cursor(&plain).unwrap();
+        assert_eq!(
+            cursor
+                .iter_grouped::<[u8; 1], [u8; 1]>()
+                .collect::<Result<Vec<_>>>()
+                .unwrap(),
+            vec![
+                (*b"a", vec![*b"1"]),
+                (*b"b", vec![*b"3"]),
+                (*b"c", vec![*b"1"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_values_of() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+        for (key, value) in [
+            (b"a", b"1"),
+            (b"b", b"1"),
+            (b"b", b"2"),
+            (b"b", b"3"),
+            (b"d", b"1"),
+        ] {
+            txn.put(&db, key, value, WriteFlags::empty()).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let mut cursor = txn.cursor(&db).unwrap();
+
+        let values = |cursor: &mut Cursor<crate::RO>, key: &[u8]| {
+            cursor
+                .values_of::<[u8; 1]>(key)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+        };
+        assert_eq!(values(&mut cursor, b"b"), vec![*b"1", *b"2", *b"3"]);
+        assert_eq!(values(&mut cursor, b"a"), vec![*b"1"]);
+        assert_eq!(values(&mut cursor, b"d"), vec![*b"1"]);
+        assert!(values(&mut cursor, b"c").is_empty());
+        assert!(values(&mut cursor, b"e").is_empty());
+    }
+
+    #[test]
+    fn test_iter_continues_from_position() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for key in [b"a", b"b", b"c", b"d"] {
+            txn.put(&db, key, b"", WriteFlags::empty()).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let mut cursor = txn.cursor(&db).unwrap();
+        let keys =
+            |iter: Iter<crate::RO, [u8; 1], ()>| iter.map(|res| res.unwrap().0).collect::<Vec<_>>();
+
+        cursor.set_range::<(), ()>(b"b").unwrap();
+        assert_eq!(keys(cursor.iter()), vec![*b"c", *b"d"]);
+
+        cursor.set_range::<(), ()>(b"b").unwrap();
+        assert_eq!(keys(cursor.iter_start()), vec![*b"a", *b"b", *b"c", *b"d"]);
+
+        cursor.set_range::<(), ()>(b"c").unwrap();
+        assert_eq!(keys(cursor.iter_from(b"b")), vec![*b"b", *b"c", *b"d"]);
+    }
+
+    #[test]
+    fn test_values_from() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+        for value in [b"1", b"2", b"3", b"4", b"5"] {
+            txn.put(&db, b"b", value, WriteFlags::empty()).unwrap();
+        }
+        txn.put(&db, b"a", b"9", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"c", b"0", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let mut cursor = txn.cursor(&db).unwrap();
+
+        let values = |cursor: &mut Cursor<crate::RO>, key: &[u8], start: &[u8]| {
+            cursor
+                .values_from::<[u8; 1]>(key, start)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+        };
+        assert_eq!(values(&mut cursor, b"b", b"3"), vec![*b"3", *b"4", *b"5"]);
+        // Resuming from a value that isn't stored starts at the next one.
+        assert_eq!(values(&mut cursor, b"b", b"25"), vec![*b"3", *b"4", *b"5"]);
+        assert_eq!(values(&mut cursor, b"b", b"5"), vec![*b"5"]);
+        assert!(values(&mut cursor, b"b", b"6").is_empty());
+        assert!(values(&mut cursor, b"a", b"a").is_empty());
+        assert!(values(&mut cursor, b"bb", b"0").is_empty());
+    }
+
+    #[test]
+    fn test_iter_raw() {
+        #[derive(Debug, PartialEq)]
+        enum Item {
+            Count(u64),
+            Name(String),
+        }
+
+        fn collect<K: TransactionKind>(cursor: &mut Cursor<K>) -> Vec<Item> {
+            let mut items = Vec::new();
+            let mut iter = cursor.iter_raw();
+            while let Some(res) = iter.advance() {
+                let (key, value) = res.unwrap();
+                items.push(match key[0] {
+                    b'c' => Item::Count(u64::from_be_bytes(<[u8; 8]>::decode(value).unwrap())),
+                    b'n' => Item::Name(String::decode(value).unwrap()),
+                    _ => unreachable!(),
+                });
+            }
+            assert!(iter.advance().is_none());
+            items
+        }
+
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+        let expected = vec![
+            Item::Count(7),
+            Item::Count(9),
+            Item::Name("alice".to_string()),
+        ];
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"c1", &7u64.to_be_bytes(), WriteFlags::empty())
+            .unwrap();
+        txn.put(&db, b"c2", &9u64.to_be_bytes(), WriteFlags::empty())
+            .unwrap();
+        txn.put(&db, b"n1", b"alice", WriteFlags::empty()).unwrap();
+        // Items still on dirty pages.
+        assert_eq!(collect(&mut txn.cursor(&db).unwrap()), expected);
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(collect(&mut txn.cursor(&db).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_iter_prefix() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for key in [
+            &b"group:1"[..],
+            b"user:1",
+            b"user:2",
+            b"users",
+            b"\xff\xff",
+            b"\xff\xff\x01",
+        ] {
+            txn.put(&db, key, b"val", WriteFlags::empty()).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let mut cursor = txn.cursor(&db).unwrap();
+
+        let keys = |iter: IterPrefix<_, Vec<u8>, ()>| {
+            iter.map(|item| item.unwrap().0).collect::<Vec<Vec<u8>>>()
+        };
+        assert_eq!(
+            keys(cursor.iter_prefix(b"user:")),
+            vec![b"user:1".to_vec(), b"user:2".to_vec()]
+        );
+        assert_eq!(
+            keys(cursor.iter_prefix(b"\xff\xff")),
+            vec![b"\xff\xff".to_vec(), b"\xff\xff\x01".to_vec()]
+        );
+        assert_eq!(keys(cursor.iter_prefix(b"")).len(), 6);
+        assert!(keys(cursor.iter_prefix(b"none")).is_empty());
+        assert!(keys(cursor.iter_prefix(b"\xff\xff\xff")).is_empty());
+    }
+
     #[test]
     fn test_iter_empty_database() {
         let dir = tempdir().unwrap();
@@ -1259,4 +2261,169 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn test_del_all_dups() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        for (key, val) in [
+            (b"key1", b"val1"),
+            (b"key2", b"val1"),
+            (b"key2", b"val2"),
+            (b"key2", b"val3"),
+            (b"key3", b"val1"),
+        ] {
+            txn.put(&db, key, val, WriteFlags::empty()).unwrap();
+        }
+
+        let mut cursor = txn.cursor(&db).unwrap();
+        assert_eq!(cursor.set(b"key2").unwrap(), Some(*b"val1"));
+        cursor.del(WriteFlags::NO_DUP_DATA).unwrap();
+        assert_eq!(cursor.set::<()>(b"key2").unwrap(), None);
+        assert_eq!(
+            cursor
+                .iter_start::<[u8; 4], [u8; 4]>()
+                .map(|r| r.unwrap())
+                .collect::<Vec<_>>(),
+            vec![(*b"key1", *b"val1"), (*b"key3", *b"val1")]
+        );
+
+        assert_eq!(cursor.set(b"key1").unwrap(), Some(*b"val1"));
+        cursor.del(WriteFlags::ALLDUPS).unwrap();
+        assert_eq!(cursor.set::<()>(b"key1").unwrap(), None);
+
+        let db = txn
+            .create_db(Some("plain"), DatabaseFlags::empty())
+            .unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+        let mut cursor = txn.cursor(&db).unwrap();
+        assert_eq!(cursor.set(b"key1").unwrap(), Some(*b"val1"));
+        cursor.del(WriteFlags::NO_DUP_DATA).unwrap();
+        assert_eq!(cursor.set::<()>(b"key1").unwrap(), None);
+        assert_eq!(cursor.set(b"key2").unwrap(), Some(*b"val2"));
+    }
+
+    #[test]
+    fn test_is_positioned() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert!(!txn.cursor(&db).unwrap().is_positioned().unwrap());
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+
+        let mut cursor = txn.cursor(&db).unwrap();
+        assert!(!cursor.is_positioned().unwrap());
+        cursor.first::<(), ()>().unwrap();
+        assert!(cursor.is_positioned().unwrap());
+        assert_eq!(cursor.prev::<(), ()>().unwrap(), None);
+        assert!(cursor.is_positioned().unwrap());
+        cursor.last::<(), ()>().unwrap();
+        assert!(cursor.is_positioned().unwrap());
+        // Stepping past either end leaves the cursor at the first or last item.
+        assert_eq!(cursor.next::<(), ()>().unwrap(), None);
+        assert!(cursor.is_positioned().unwrap());
+        assert_eq!(cursor.get_current().unwrap(), Some((*b"key2", *b"val2")));
+        assert_eq!(cursor.set::<()>(b"key3").unwrap(), None);
+        assert!(!cursor.is_positioned().unwrap());
+        cursor.set::<()>(b"key2").unwrap();
+        assert!(cursor.is_positioned().unwrap());
+    }
+
+    #[test]
+    fn test_cursor_dup() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert!(matches!(txn.cursor_dup(&db), Err(Error::Incompatible)));
+
+        let db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        for data in [b"val1", b"val2", b"val3"] {
+            txn.put(&db, b"key1", data, WriteFlags::empty()).unwrap();
+        }
+        let mut cursor = txn.cursor_dup(&db).unwrap();
+        assert_eq!(cursor.get_both(b"key1", b"val2").unwrap(), Some(*b"val2"));
+        assert_eq!(cursor.next_dup().unwrap(), Some((*b"key1", *b"val3")));
+        assert_eq!(cursor.first_dup().unwrap(), Some(*b"val1"));
+        cursor.into_inner().del(WriteFlags::empty()).unwrap();
+        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val2"));
+    }
+
+    #[test]
+    fn test_del_dup() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        for data in [b"val1", b"val2", b"val3"] {
+            txn.put(&db, b"key1", data, WriteFlags::empty()).unwrap();
+        }
+        txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+
+        let mut cursor = txn.cursor(&db).unwrap();
+        assert!(cursor.del_dup(b"key1", b"val2").unwrap());
+        assert!(!cursor.del_dup(b"key1", b"val2").unwrap());
+        assert!(!cursor.del_dup(b"key3", b"val2").unwrap());
+        assert_eq!(
+            cursor
+                .iter_start::<[u8; 4], [u8; 4]>()
+                .collect::<Result<Vec<_>>>()
+                .unwrap(),
+            vec![
+                (*b"key1", *b"val1"),
+                (*b"key1", *b"val3"),
+                (*b"key2", *b"val2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reserve() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let mut cursor = txn.cursor(&db).unwrap();
+        cursor.put(b"key1", b"val1", WriteFlags::empty()).unwrap();
+        cursor
+            .reserve(b"key2", 4, WriteFlags::empty())
+            .unwrap()
+            .copy_from_slice(b"val2");
+        assert_eq!(cursor.get_current().unwrap(), Some((*b"key2", *b"val2")));
+
+        // Overwrite the current value with one of a different length.
+        cursor.first::<(), ()>().unwrap();
+        cursor
+            .reserve(b"key1", 6, WriteFlags::CURRENT)
+            .unwrap()
+            .copy_from_slice(b"value1");
+        drop(cursor);
+        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"value1"));
+        assert_eq!(txn.get(&db, b"key2").unwrap(), Some(*b"val2"));
+
+        let dup_db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        let mut cursor = txn.cursor(&dup_db).unwrap();
+        assert!(matches!(
+            cursor.reserve(b"key1", 4, WriteFlags::empty()),
+            Err(Error::Incompatible)
+        ));
+    }
 }
@@ -10,6 +10,10 @@ use std::{ffi::CString, marker::PhantomData, ptr};
 /// A handle to an individual database in an environment.
 ///
 /// A database handle denotes the name and parameters of a database in an environment.
+///
+/// Handles stay open for the lifetime of the environment, unless it was opened with
+/// [EnvironmentBuilder::set_close_dbs()](crate::EnvironmentBuilder::set_close_dbs), in which
+/// case they are closed once every transaction that opened them has ended.
 #[derive(Debug)]
 pub struct Database<'txn> {
     dbi: ffi::MDBX_dbi,
@@ -25,6 +29,20 @@ impl<'txn> Database<'txn> {
         txn: &'txn Transaction<'env, K, E>,
         name: Option<&str>,
         flags: c_uint,
+    ) -> Result<Self> {
+        let dbi = txn_execute(&*txn.txn_mutex(), |raw| {
+            txn.env().open_dbi(txn.held_dbis(), || unsafe {
+                Self::open_raw(raw, name, flags).map(|db| db.dbi)
+            })
+        })?;
+        Ok(Self::new_from_ptr(dbi))
+    }
+
+    /// Opens a new database handle in the given raw transaction, which must be live.
+    pub(crate) unsafe fn open_raw(
+        txn: *mut ffi::MDBX_txn,
+        name: Option<&str>,
+        flags: c_uint,
     ) -> Result<Self> {
         let c_name = name.map(|n| CString::new(n).unwrap());
         let name_ptr = if let Some(c_name) = &c_name {
@@ -33,9 +51,7 @@ impl<'txn> Database<'txn> {
             ptr::null()
         };
         let mut dbi: ffi::MDBX_dbi = 0;
-        mdbx_result(txn_execute(&*txn.txn_mutex(), |txn| unsafe {
-            ffi::mdbx_dbi_open(txn, name_ptr, flags, &mut dbi)
-        }))?;
+        mdbx_result(ffi::mdbx_dbi_open(txn, name_ptr, flags, &mut dbi))?;
         Ok(Self::new_from_ptr(dbi))
     }
 
@@ -53,6 +69,14 @@ impl<'txn> Database<'txn> {
         }
     }
 
+    /// The unnamed database, whose handle MDBX never changes or closes.
+    pub(crate) fn main_db() -> Self {
+        Database {
+            dbi: 1,
+            _marker: PhantomData,
+        }
+    }
+
     /// Returns the underlying MDBX database handle.
     ///
     /// The caller **must** ensure that the handle is not used after the lifetime of the
@@ -1,27 +1,37 @@
 use crate::{
     database::Database,
     error::{mdbx_result, Error, Result},
-    flags::EnvironmentFlags,
-    transaction::{RO, RW},
-    Mode, Transaction, TransactionKind,
+    flags::{CopyFlags, DatabaseFlags, EnvironmentFlags},
+    transaction::{txn_execute, LocalTransaction, PooledTransaction, RO, RW},
+    Mode, SyncMode, Transaction, TransactionKind,
 };
 use byteorder::{ByteOrder, NativeEndian};
-use libc::c_uint;
+use indexmap::IndexSet;
+use libc::{c_char, c_int, c_uint, c_void};
 use mem::size_of;
+use parking_lot::Mutex;
 #[cfg(unix)]
-use std::os::unix::ffi::OsStrExt;
+use std::os::unix::{ffi::OsStrExt, io::RawFd};
 use std::{
-    ffi::CString,
+    any::Any,
+    borrow::Cow,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    ffi::{CStr, CString},
     fmt,
     fmt::Debug,
     marker::PhantomData,
     mem,
     ops::{Bound, RangeBounds},
+    panic,
     path::Path,
-    ptr, result,
-    sync::mpsc::{sync_channel, SyncSender},
-    thread::sleep,
-    time::Duration,
+    process, ptr, result,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        mpsc::{sync_channel, SyncSender},
+        Arc,
+    },
+    thread::{sleep, JoinHandle},
+    time::{Duration, Instant},
 };
 
 mod private {
@@ -33,12 +43,26 @@ mod private {
     impl<'env> Sealed for WriteMap {}
 }
 
+/// Selects how an [Environment] maps its data file, chosen by the type parameter, e.g.
+/// `Environment::<WriteMap>::new()`.
 pub trait EnvironmentKind: private::Sealed + Debug + 'static {
     const EXTRA_FLAGS: ffi::MDBX_env_flags_t;
 }
 
+/// The data file is mapped read-only and written through regular file I/O.
+///
+/// This is the safe default: stray writes through a pointer into the map fault instead of
+/// corrupting the database, and nested transactions are available through
+/// [Transaction::begin_nested_txn()](crate::Transaction::begin_nested_txn).
 #[derive(Debug)]
 pub struct NoWriteMap;
+
+/// The data file is mapped read-write (`MDBX_WRITEMAP`) and written to directly in memory.
+///
+/// Writes are usually faster and use less memory, as no dirty pages are kept on the side. The
+/// tradeoff is that the whole map is writable, so a stray write through a pointer into it can
+/// silently corrupt the database, and nested transactions are not supported: use [NoWriteMap]
+/// if you need them.
 #[derive(Debug)]
 pub struct WriteMap;
 
@@ -73,6 +97,11 @@ pub(crate) enum TxnManagerMessage {
         tx: TxnPtr,
         sender: SyncSender<Result<bool>>,
     },
+    CommitAndBegin {
+        tx: TxnPtr,
+        flags: ffi::MDBX_txn_flags_t,
+        sender: SyncSender<Result<(bool, Result<TxnPtr>)>>,
+    },
 }
 
 /// An environment supports multiple databases, all residing in the same shared-memory map.
@@ -82,9 +111,42 @@ where
 {
     env: *mut ffi::MDBX_env,
     pub(crate) txn_manager: Option<SyncSender<TxnManagerMessage>>,
+    txn_manager_thread: Option<JoinHandle<()>>,
+    ro_txn_pool: Mutex<Vec<TxnPtr>>,
+    ro_txn_pool_size: usize,
+    read_only: bool,
+    sync_on_drop: bool,
+    close_dbs: bool,
+    handlers: Box<Handlers>,
+    dbi_refs: Mutex<DbiRefs>,
     _marker: PhantomData<E>,
 }
 
+/// Callbacks installed on an environment, reachable from MDBX through its user context.
+#[derive(Default)]
+struct Handlers {
+    assert: AtomicPtr<()>,
+    hsr: AtomicPtr<()>,
+    userctx: Mutex<Option<Arc<dyn Any + Send + Sync>>>,
+}
+
+/// The free list and main databases, which MDBX never closes.
+const CORE_DBS: ffi::MDBX_dbi = 2;
+
+/// Tracks which transactions hold each database handle, so that handles can be closed once they
+/// are no longer used, see [EnvironmentBuilder::set_close_dbs()].
+///
+/// A [Database] can't outlive the transaction it was opened in, but the transaction may have
+/// modified the database after dropping it, and MDBX requires that handles are not closed before
+/// such a transaction ends. So each transaction holds a reference to every handle it opened until
+/// it ends, and handles are closed when the last reference goes away, unless they were made
+/// permanent.
+#[derive(Default)]
+struct DbiRefs {
+    counts: HashMap<ffi::MDBX_dbi, usize>,
+    permanent: HashSet<ffi::MDBX_dbi>,
+}
+
 impl<E> Environment<E>
 where
     E: EnvironmentKind,
@@ -103,6 +165,10 @@ where
             spill_max_denominator: None,
             spill_min_denominator: None,
             geometry: None,
+            page_size: None,
+            ro_txn_pool_size: 0,
+            sync_on_drop: true,
+            close_dbs: false,
             _marker: PhantomData,
         }
     }
@@ -115,25 +181,213 @@ where
         self.env
     }
 
+    /// Calls `f` with the raw MDBX environment, for MDBX functions that have no wrapper in this
+    /// crate.
+    ///
+    /// # Safety
+    /// `f` must not close the environment, change options that can only be set before it is
+    /// opened, or otherwise break the assumptions made by this crate, and must not keep the
+    /// pointer past its return. Transactions begun through the pointer are not tracked by this
+    /// crate and must be ended by `f` itself.
+    pub unsafe fn with_raw_env<T>(&self, f: impl FnOnce(*mut ffi::MDBX_env) -> T) -> T {
+        f(self.env)
+    }
+
+    /// Installs a handler for failed internal MDBX assertions, or removes it with [None].
+    ///
+    /// Without a handler MDBX aborts the process. If the handler returns, MDBX carries on as if
+    /// the assertion had passed, so the environment should be treated as suspect from then on.
+    /// A panicking handler aborts the process, as it can't unwind through MDBX.
+    ///
+    /// Assertions are only compiled into libmdbx built with `MDBX_DEBUG`; otherwise installing a
+    /// handler fails with [Error::Other] holding `MDBX_ENOSYS`. Handlers are reached through the
+    /// environment's user context, which must not be changed through
+    /// [Environment::with_raw_env()] while one is installed.
+    pub fn set_assert_handler(&self, handler: Option<AssertFn>) -> Result<()> {
+        unsafe {
+            self.install_handlers()?;
+            match handler {
+                Some(handler) => {
+                    self.handlers
+                        .assert
+                        .store(handler as *mut (), Ordering::SeqCst);
+                    if let Err(e) =
+                        mdbx_result(ffi::mdbx_env_set_assert(self.env, Some(assert_trampoline)))
+                    {
+                        self.handlers
+                            .assert
+                            .store(ptr::null_mut(), Ordering::SeqCst);
+                        return Err(e);
+                    }
+                }
+                None => {
+                    match mdbx_result(ffi::mdbx_env_set_assert(self.env, None)) {
+                        Ok(_) | Err(Error::Other(ffi::MDBX_ENOSYS)) => (),
+                        Err(e) => return Err(e),
+                    }
+                    self.handlers
+                        .assert
+                        .store(ptr::null_mut(), Ordering::SeqCst);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Installs a handler for slow readers, or removes it with [None].
+    ///
+    /// When a write transaction runs out of space because an old read transaction keeps pages
+    /// from being reused, MDBX calls the handler with the oldest such reader before growing the
+    /// datafile or failing with [Error::MapFull]. The handler may wait for the reader, signal
+    /// or kill it, and must return the [HsrDecision] matching what it did. It is called on the
+    /// thread performing the write, with the transaction lock held, so it must not use the
+    /// writing transaction. A panicking handler is treated as [HsrDecision::GiveUp].
+    ///
+    /// Like [Environment::set_assert_handler()], the handler is reached through the
+    /// environment's user context.
+    pub fn set_hsr(&self, handler: Option<HsrFn>) -> Result<()> {
+        unsafe {
+            self.install_handlers()?;
+            match handler {
+                Some(handler) => {
+                    self.handlers
+                        .hsr
+                        .store(handler as *mut (), Ordering::SeqCst);
+                    mdbx_result(ffi::mdbx_env_set_hsr(self.env, Some(hsr_trampoline)))?;
+                }
+                None => {
+                    mdbx_result(ffi::mdbx_env_set_hsr(self.env, None))?;
+                    self.handlers.hsr.store(ptr::null_mut(), Ordering::SeqCst);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Attaches an application-defined context to the environment, replacing any previous one.
+    ///
+    /// MDBX's own user context pointer is taken by the handlers installed with
+    /// [Environment::set_hsr()] and [Environment::set_assert_handler()], so the context is kept
+    /// alongside them instead. It is dropped when the environment is closed.
+    pub fn set_userctx<T>(&self, ctx: Arc<T>)
+    where
+        T: Any + Send + Sync,
+    {
+        *self.handlers.userctx.lock() = Some(ctx);
+    }
+
+    /// Returns the context attached with [Environment::set_userctx()], or [None] if there is none
+    /// or it is not a `T`.
+    pub fn userctx<T>(&self) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        self.handlers.userctx.lock().clone()?.downcast().ok()
+    }
+
+    /// Points the environment's user context at [Handlers].
+    unsafe fn install_handlers(&self) -> Result<()> {
+        let handlers = &*self.handlers as *const Handlers as *mut c_void;
+        if ffi::mdbx_env_get_userctx(self.env) != handlers {
+            mdbx_result(ffi::mdbx_env_set_userctx(self.env, handlers))?;
+        }
+        Ok(())
+    }
+
     /// Create a read-only transaction for use with the environment.
     pub fn begin_ro_txn(&self) -> Result<Transaction<'_, RO, E>> {
         Transaction::new(self)
     }
 
+    /// Create a read-only transaction, and return it along with the id of the snapshot it reads.
+    ///
+    /// The transaction sees the database as of that snapshot for its whole lifetime, regardless
+    /// of any writes committed after it began, so the id can be used to correlate what it read.
+    pub fn begin_ro_txn_with_id(&self) -> Result<(Transaction<'_, RO, E>, u64)> {
+        let txn = self.begin_ro_txn()?;
+        let id = txn.id();
+        Ok((txn, id))
+    }
+
+    /// Create a read-only transaction, and return it along with a handle to the unnamed database.
+    ///
+    /// This is equivalent to calling [Transaction::open_db()] with [None], but saves a call into
+    /// MDBX, as the handle of the unnamed database is always the same.
+    pub fn default_db_txn(&self) -> Result<(Transaction<'_, RO, E>, Database<'_>)> {
+        Ok((self.begin_ro_txn()?, Database::main_db()))
+    }
+
+    /// Opens a database and returns a handle that remains valid for the lifetime of the
+    /// environment, so it can be used in any transaction without opening it again.
+    ///
+    /// The database is opened in a short transaction of its own, which creates it with `flags`
+    /// if it doesn't exist, see [Transaction::create_db()]. In a read-only environment it must
+    /// already exist, and is opened with the flags it was created with.
+    pub fn open_db_permanent(
+        &self,
+        name: Option<&str>,
+        flags: DatabaseFlags,
+    ) -> Result<Database<'_>> {
+        let mut dbs = if self.read_only {
+            let txn = self.begin_ro_txn()?;
+            txn.prime_for_permaopen(txn.open_db(name)?);
+            txn.commit_and_rebind_open_dbs()?.1
+        } else {
+            let txn = self.begin_rw_txn()?;
+            txn.prime_for_permaopen(txn.create_db(name, flags)?);
+            txn.commit_and_rebind_open_dbs()?.1
+        };
+        Ok(dbs.remove(0))
+    }
+
+    /// Create a read-only transaction that can only be used from the current thread, but avoids
+    /// locking on every operation.
+    pub fn begin_ro_txn_local(&self) -> Result<LocalTransaction<'_, E>> {
+        LocalTransaction::new(self)
+    }
+
+    /// Take a read-only transaction from the environment's pool, or create one if the pool is
+    /// empty.
+    ///
+    /// A pooled transaction is renewed before it is handed out, so it always reads the latest
+    /// committed data. When the returned guard is dropped the transaction is reset and returned to
+    /// the pool, up to the size set with [EnvironmentBuilder::set_ro_txn_pool_size()].
+    pub fn ro_txn(&self) -> Result<PooledTransaction<'_, E>> {
+        let pooled = self.ro_txn_pool.lock().pop();
+        let txn = match pooled {
+            Some(txn) => unsafe {
+                if let Err(e) = mdbx_result(ffi::mdbx_txn_renew(txn.0)) {
+                    ffi::mdbx_txn_abort(txn.0);
+                    return Err(e);
+                }
+                Transaction::new_from_ptr(self, txn.0)
+            },
+            None => Transaction::new(self)?,
+        };
+        Ok(PooledTransaction::new(txn))
+    }
+
+    /// Return a reset read-only transaction to the pool, aborting it if the pool is full.
+    pub(crate) fn release_ro_txn(&self, txn: TxnPtr) {
+        let mut pool = self.ro_txn_pool.lock();
+        if pool.len() < self.ro_txn_pool_size {
+            pool.push(txn);
+        } else {
+            unsafe {
+                ffi::mdbx_txn_abort(txn.0);
+            }
+        }
+    }
+
     /// Create a read-write transaction for use with the environment. This method will block while
     /// there are any other read-write transactions open on the environment.
     pub fn begin_rw_txn(&self) -> Result<Transaction<'_, RW, E>> {
-        let sender = self.txn_manager.as_ref().ok_or(Error::Access)?;
         let txn = loop {
-            let (tx, rx) = sync_channel(0);
-            sender
-                .send(TxnManagerMessage::Begin {
-                    parent: TxnPtr(ptr::null_mut()),
-                    flags: RW::OPEN_FLAGS,
-                    sender: tx,
-                })
-                .unwrap();
-            let res = rx.recv().unwrap();
+            let res = self.txn_manager_call(|sender| TxnManagerMessage::Begin {
+                parent: TxnPtr(ptr::null_mut()),
+                flags: RW::OPEN_FLAGS,
+                sender,
+            });
             if let Err(Error::Busy) = &res {
                 sleep(Duration::from_millis(250));
                 continue;
@@ -144,11 +398,197 @@ where
         Ok(Transaction::new_from_ptr(self, txn.0))
     }
 
+    /// Create a read-write transaction for use with the environment, without blocking.
+    ///
+    /// Returns [None] if another read-write transaction is already open on the environment.
+    /// `MDBX_TXN_TRY` is not passed through: libmdbx 0.11 leaves it set on the resulting
+    /// transaction and trips an internal assertion, so this relies on the transaction manager
+    /// thread already owning the write lock to get `MDBX_BUSY` back immediately. A writer in
+    /// another process will still be waited on.
+    pub fn begin_rw_txn_try(&self) -> Result<Option<Transaction<'_, RW, E>>> {
+        let res = self.txn_manager_call(|sender| TxnManagerMessage::Begin {
+            parent: TxnPtr(ptr::null_mut()),
+            flags: RW::OPEN_FLAGS,
+            sender,
+        });
+        match res {
+            Ok(txn) => Ok(Some(Transaction::new_from_ptr(self, txn.0))),
+            Err(Error::Busy) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Create a read-write transaction for use with the environment, waiting at most `timeout`
+    /// for other read-write transactions to finish.
+    ///
+    /// Returns [None] if the timeout elapses first. Like [Environment::begin_rw_txn()], this
+    /// retries periodically while another transaction in this process holds the write lock, so
+    /// a transaction is never granted after giving up on it. As with
+    /// [Environment::begin_rw_txn_try()], a writer in another process is waited on regardless.
+    pub fn begin_rw_txn_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<Transaction<'_, RW, E>>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let res = self.txn_manager_call(|sender| TxnManagerMessage::Begin {
+                parent: TxnPtr(ptr::null_mut()),
+                flags: RW::OPEN_FLAGS,
+                sender,
+            });
+            match res {
+                Ok(txn) => return Ok(Some(Transaction::new_from_ptr(self, txn.0))),
+                Err(Error::Busy) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Ok(None);
+                    }
+                    sleep((deadline - now).min(Duration::from_millis(250)));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Opens a database handle with `open` on behalf of a transaction, which takes a reference
+    /// to it unless `held` shows it already has one. Handles are only tracked if the environment
+    /// closes them once unused.
+    pub(crate) fn open_dbi(
+        &self,
+        held: &Mutex<IndexSet<ffi::MDBX_dbi>>,
+        open: impl FnOnce() -> Result<ffi::MDBX_dbi>,
+    ) -> Result<ffi::MDBX_dbi> {
+        if !self.close_dbs {
+            return open();
+        }
+        // Held across the open, so a concurrent release can't close the handle in between.
+        let mut refs = self.dbi_refs.lock();
+        let dbi = open()?;
+        if dbi >= CORE_DBS && held.lock().insert(dbi) {
+            *refs.counts.entry(dbi).or_default() += 1;
+        }
+        Ok(dbi)
+    }
+
+    /// Drops the references of a transaction that has ended, closing the handles no other
+    /// transaction holds.
+    pub(crate) fn release_dbis(&self, held: impl IntoIterator<Item = ffi::MDBX_dbi>) {
+        let refs = &mut *self.dbi_refs.lock();
+        for dbi in held {
+            if let Entry::Occupied(mut count) = refs.counts.entry(dbi) {
+                *count.get_mut() -= 1;
+                if *count.get() == 0 {
+                    count.remove();
+                    if !refs.permanent.contains(&dbi) {
+                        // Fails harmlessly if MDBX already closed the handle, e.g. after the
+                        // database was dropped or its creation was aborted.
+                        unsafe { ffi::mdbx_dbi_close(self.env, dbi) };
+                    }
+                }
+            }
+        }
+    }
+
+    /// Keeps a database handle open for the lifetime of the environment.
+    pub(crate) fn pin_dbi(&self, dbi: ffi::MDBX_dbi) {
+        self.dbi_refs.lock().permanent.insert(dbi);
+    }
+
+    /// Sends a request to the transaction manager thread and waits for its reply.
+    ///
+    /// Returns [Error::Access] for read-only environments, which have no transaction manager, and
+    /// [Error::TxnManagerDead] if the transaction manager thread has stopped.
+    pub(crate) fn txn_manager_call<T>(
+        &self,
+        msg: impl FnOnce(SyncSender<Result<T>>) -> TxnManagerMessage,
+    ) -> Result<T> {
+        let txn_manager = self.txn_manager.as_ref().ok_or(Error::Access)?;
+        let (sender, rx) = sync_channel(0);
+        txn_manager
+            .send(msg(sender))
+            .map_err(|_| Error::TxnManagerDead)?;
+        rx.recv().map_err(|_| Error::TxnManagerDead)?
+    }
+
+    /// Close the environment, returning any error reported by MDBX.
+    ///
+    /// Dropping an [Environment] closes it as well, but has no way to report a failure. The
+    /// borrow checker guarantees that no transactions are open at this point; pooled read-only
+    /// transactions are aborted and the transaction manager thread is shut down before closing.
+    ///
+    /// Unless disabled with [EnvironmentBuilder::set_sync_on_drop()], data buffers are flushed to
+    /// disk first, as with [Self::sync()].
+    pub fn close(mut self) -> Result<()> {
+        self.shutdown();
+        let env = mem::replace(&mut self.env, ptr::null_mut());
+        mdbx_result(unsafe { ffi::mdbx_env_close_ex(env, !self.sync_on_drop) })?;
+        Ok(())
+    }
+
+    fn shutdown(&mut self) {
+        for txn in self.ro_txn_pool.get_mut().drain(..) {
+            unsafe {
+                ffi::mdbx_txn_abort(txn.0);
+            }
+        }
+        // Dropping the sender stops the transaction manager loop.
+        self.txn_manager = None;
+        if let Some(handle) = self.txn_manager_thread.take() {
+            let _ = handle.join();
+        }
+    }
+
     /// Flush the environment data buffers to disk.
     pub fn sync(&self, force: bool) -> Result<bool> {
         mdbx_result(unsafe { ffi::mdbx_env_sync_ex(self.env(), force, false) })
     }
 
+    /// Flushes the environment data buffers to disk if that can be done without waiting, e.g. from
+    /// an idle loop in a [SyncMode::SafeNoSync] environment.
+    ///
+    /// Returns `true` if data was flushed, and `false` if there was nothing to flush or the lock
+    /// needed for it is held elsewhere, e.g. by a writer or another sync in progress.
+    pub fn sync_nonblocking(&self) -> Result<bool> {
+        match mdbx_result(unsafe { ffi::mdbx_env_sync_ex(self.env(), true, true) }) {
+            Ok(nothing_to_sync) => Ok(!nothing_to_sync),
+            Err(Error::Busy) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes a consistent copy of the environment to `fd`, which must be open for writing.
+    ///
+    /// The copy is taken from a read-only snapshot, so writers are not blocked while it runs.
+    /// `fd` may be a pipe or socket, e.g. to stream a backup to another process without a
+    /// temporary file. MDBX refuses to write to a non-seekable `fd` if the environment has
+    /// leaked pages, in which case the error it reports is returned.
+    ///
+    /// The caller keeps ownership of `fd`; it is not closed.
+    #[cfg(unix)]
+    pub fn copy_to_fd(&self, fd: RawFd, flags: CopyFlags) -> Result<()> {
+        mdbx_result(unsafe { ffi::mdbx_env_copy2fd(self.env(), fd, flags.bits()) })?;
+        Ok(())
+    }
+
+    /// Changes the upper bound of the map size, keeping the rest of the geometry as it is.
+    ///
+    /// This is the usual way to recover from [Error::MapFull]: grow the map and retry the
+    /// transaction. Shrinking below the space currently in use fails with [Error::MapFull].
+    ///
+    /// The map can only grow within the address space already reserved for it, which is the
+    /// upper bound the environment was opened with unless the addresses after it happen to be
+    /// free. Otherwise this fails with [Error::UnableExtendMapsize] and the environment has to be
+    /// reopened with a larger [Geometry].
+    ///
+    /// MDBX takes the write lock to apply the change, so this blocks until any active write
+    /// transaction finishes, and must not be called while the calling thread holds one.
+    pub fn resize_map(&self, new_upper: usize) -> Result<()> {
+        mdbx_result(unsafe {
+            ffi::mdbx_env_set_geometry(self.env(), -1, -1, new_upper as isize, -1, -1, -1)
+        })?;
+        Ok(())
+    }
+
     /// Retrieves statistics about this environment.
     pub fn stat(&self) -> Result<Stat> {
         unsafe {
@@ -177,6 +617,78 @@ where
         }
     }
 
+    /// Returns true if the environment was opened with [Mode::ReadOnly], in which case
+    /// [Self::begin_rw_txn()] always fails.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Retrieves the database page size of the environment.
+    pub fn page_size(&self) -> Result<usize> {
+        Ok(self.stat()?.page_size() as usize)
+    }
+
+    /// Retrieves the id of the most recently committed transaction, without starting a read
+    /// transaction.
+    ///
+    /// A newly created environment reports the id of its initial meta page.
+    pub fn latest_txn_id(&self) -> Result<u64> {
+        Ok(self.info()?.0.mi_recent_txnid)
+    }
+
+    /// Retrieves the maximum number of reader slots for the environment.
+    ///
+    /// This may be larger than the value passed to
+    /// [EnvironmentBuilder::set_max_readers()], since MDBX rounds it up to fill the lock file.
+    pub fn max_readers(&self) -> Result<u32> {
+        let mut max_readers: u64 = 0;
+        mdbx_result(unsafe {
+            ffi::mdbx_env_get_option(self.env(), ffi::MDBX_opt_max_readers, &mut max_readers)
+        })?;
+        Ok(max_readers as u32)
+    }
+
+    /// Retrieves the maximum number of named databases in the environment, as set with
+    /// [EnvironmentBuilder::set_max_dbs()].
+    pub fn max_dbs(&self) -> Result<u32> {
+        let mut max_dbs: u64 = 0;
+        mdbx_result(unsafe {
+            ffi::mdbx_env_get_option(self.env(), ffi::MDBX_opt_max_db, &mut max_dbs)
+        })?;
+        Ok(max_dbs as u32)
+    }
+
+    /// Retrieves the maximum size of keys that can be written to a database with the given
+    /// flags, which depends on the page size and e.g. [DatabaseFlags::DUP_SORT].
+    pub fn max_key_size(&self, flags: DatabaseFlags) -> Result<usize> {
+        match unsafe { ffi::mdbx_env_get_maxkeysize_ex(self.env(), flags.bits()) } {
+            -1 => Err(Error::InvalidValue),
+            size => Ok(size as usize),
+        }
+    }
+
+    /// Retrieves the maximum size of keys that can be written to a database without flags, see
+    /// [Environment::max_key_size()].
+    pub fn max_key_size_default(&self) -> Result<usize> {
+        self.max_key_size(DatabaseFlags::empty())
+    }
+
+    /// Retrieves the maximum size of values that can be written to a database with the given
+    /// flags. With [DatabaseFlags::DUP_SORT] values are stored like keys, so the limit is much
+    /// smaller.
+    pub fn max_value_size(&self, flags: DatabaseFlags) -> Result<usize> {
+        match unsafe { ffi::mdbx_env_get_maxvalsize_ex(self.env(), flags.bits()) } {
+            -1 => Err(Error::InvalidValue),
+            size => Ok(size as usize),
+        }
+    }
+
+    /// Retrieves the maximum size of values that can be written to a database without flags,
+    /// see [Environment::max_value_size()].
+    pub fn max_value_size_default(&self) -> Result<usize> {
+        self.max_value_size(DatabaseFlags::empty())
+    }
+
     /// Retrieves the total number of pages on the freelist.
     ///
     /// Along with [Environment::info()], this can be used to calculate the exact number
@@ -196,37 +708,134 @@ where
     /// let pgs_free = total_pgs - pgs_in_use;
     /// ```
     ///
+    /// Note: it will create a read transaction to traverse the freelist database. See
+    /// [Environment::freelist_pages()].
+    pub fn freelist(&self) -> Result<usize> {
+        Ok(self.freelist_pages()? as usize)
+    }
+
+    /// Retrieves the total number of pages currently held by the garbage collector.
+    ///
+    /// A freelist that keeps growing usually means pages are being retained by long-running
+    /// readers, or that the database sees a lot of churn.
+    ///
     /// Note:
     ///
-    /// * LMDB stores all the freelists in the designated database 0 in each environment,
-    ///   and the freelist count is stored at the beginning of the value as `libc::size_t`
-    ///   in the native byte order.
+    /// * MDBX stores the garbage collector records in the designated database 0 in each
+    ///   environment, and each record starts with its page count as a 32-bit page number in
+    ///   the native byte order.
     ///
     /// * It will create a read transaction to traverse the freelist database.
-    pub fn freelist(&self) -> Result<usize> {
-        let mut freelist: usize = 0;
+    pub fn freelist_pages(&self) -> Result<u64> {
+        let mut freelist: u64 = 0;
         let txn = self.begin_ro_txn()?;
         let db = Database::freelist_db();
         let cursor = txn.cursor(&db)?;
 
         for result in cursor {
             let (_key, value) = result?;
-            if value.len() < mem::size_of::<usize>() {
+            if value.len() < mem::size_of::<u32>() {
                 return Err(Error::Corrupted);
             }
 
-            let s = &value[..mem::size_of::<usize>()];
-            if cfg!(target_pointer_width = "64") {
-                freelist += NativeEndian::read_u64(s) as usize;
+            freelist += u64::from(NativeEndian::read_u32(&value[..mem::size_of::<u32>()]));
+        }
+
+        Ok(freelist)
+    }
+
+    /// Checks the structural integrity of the environment by walking the B-trees of all its
+    /// databases in a read transaction, as the `mdbx_chk` tool does.
+    ///
+    /// Problems with individual pages, such as broken pages or keys out of order, are collected
+    /// in the returned [ValidationReport]. An error is returned only if the walk itself could not
+    /// be completed.
+    pub fn validate(&self) -> Result<ValidationReport> {
+        unsafe extern "C" fn visitor(
+            pgno: u64,
+            number: c_uint,
+            ctx: *mut c_void,
+            _deep: c_int,
+            dbi: *const c_char,
+            _page_size: usize,
+            page_type: ffi::MDBX_page_type_t,
+            err: ffi::MDBX_error_t,
+            _nentries: usize,
+            _payload_bytes: usize,
+            _header_bytes: usize,
+            _unused_bytes: usize,
+        ) -> c_int {
+            let report = &mut *(ctx as *mut ValidationReport);
+            let is_subpage = matches!(
+                page_type,
+                ffi::MDBX_subpage_leaf | ffi::MDBX_subpage_dupfixed_leaf | ffi::MDBX_subpage_broken
+            );
+            if !is_subpage {
+                report.pages += u64::from(number);
+            }
+
+            let error = if err != ffi::MDBX_SUCCESS {
+                Some(Error::from_err_code(err))
+            } else if matches!(page_type, ffi::MDBX_page_broken | ffi::MDBX_subpage_broken) {
+                Some(Error::Corrupted)
             } else {
-                freelist += NativeEndian::read_u32(s) as usize;
+                None
+            };
+            if let Some(error) = error {
+                // The main database, the GC and the meta pages are passed as small sentinel
+                // values rather than names.
+                let db = match dbi as isize {
+                    -2..=0 => None,
+                    _ => Some(CStr::from_ptr(dbi).to_string_lossy().into_owned()),
+                };
+                report.issues.push(ValidationIssue { pgno, db, error });
             }
+            ffi::MDBX_SUCCESS
         }
 
-        Ok(freelist)
+        let txn = self.begin_ro_txn()?;
+        let mut report = ValidationReport::default();
+        mdbx_result(txn_execute(&txn.txn_mutex(), |txn| unsafe {
+            ffi::mdbx_env_pgwalk(
+                txn,
+                Some(visitor),
+                &mut report as *mut ValidationReport as *mut c_void,
+                false,
+            )
+        }))?;
+        Ok(report)
+    }
+}
+
+/// The result of [Environment::validate()].
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    /// Number of pages in use by the B-trees, including the meta pages. Together with
+    /// [Environment::freelist_pages()] this accounts for every allocated page.
+    pub pages: u64,
+    /// Problems found during the walk, in the order they were found.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns whether no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
     }
 }
 
+/// A problem found by [Environment::validate()].
+#[derive(Debug)]
+pub struct ValidationIssue {
+    /// Number of the offending page.
+    pub pgno: u64,
+    /// Name of the database the page belongs to, or [None] for the main database and for pages
+    /// used by MDBX itself.
+    pub db: Option<String>,
+    /// What is wrong with the page.
+    pub error: Error,
+}
+
 /// Environment statistics.
 ///
 /// Contains information about the size and layout of an MDBX environment or database.
@@ -281,15 +890,60 @@ impl Stat {
     pub fn entries(&self) -> usize {
         self.0.ms_entries as usize
     }
+
+    /// Id of the last committed transaction that modified the database.
+    #[inline]
+    pub fn mod_txnid(&self) -> u64 {
+        self.0.ms_mod_txnid
+    }
+
+    /// Number of bytes in the pages of the database, i.e. the leaf, branch and overflow pages
+    /// multiplied by the page size.
+    ///
+    /// This includes the free space within those pages, so it is an upper bound on the size of
+    /// the stored data.
+    #[inline]
+    pub fn total_size_bytes(&self) -> u64 {
+        (self.0.ms_leaf_pages + self.0.ms_branch_pages + self.0.ms_overflow_pages)
+            * u64::from(self.0.ms_psize)
+    }
 }
 
+/// The current geometry of the datafile, see [Info::geometry()].
+///
+/// All sizes are in bytes.
 #[repr(transparent)]
 pub struct GeometryInfo(ffi::MDBX_envinfo__bindgen_ty_1);
 
 impl GeometryInfo {
+    /// Lower limit for the datafile size.
     pub fn min(&self) -> u64 {
         self.0.lower
     }
+
+    /// Upper limit for the datafile size.
+    #[inline]
+    pub fn max(&self) -> u64 {
+        self.0.upper
+    }
+
+    /// Current datafile size.
+    #[inline]
+    pub fn current(&self) -> u64 {
+        self.0.current
+    }
+
+    /// Shrink threshold for the datafile.
+    #[inline]
+    pub fn shrink_threshold(&self) -> u64 {
+        self.0.shrink
+    }
+
+    /// Growth step for the datafile.
+    #[inline]
+    pub fn growth_step(&self) -> u64 {
+        self.0.grow
+    }
 }
 
 /// Environment information.
@@ -332,6 +986,137 @@ impl Info {
     pub fn num_readers(&self) -> usize {
         self.0.mi_numreaders as usize
     }
+
+    /// Identifier of the current OS boot, or zero if the system doesn't provide one.
+    ///
+    /// MDBX compares this against the boot id recorded at the last steady sync when opening the
+    /// database; if they differ, changes made since then with a non-durable [SyncMode] are rolled
+    /// back.
+    #[inline]
+    pub fn bootid(&self) -> u128 {
+        let current = self.0.mi_bootid.current;
+        u128::from(current.x) << 64 | u128::from(current.y)
+    }
+}
+
+/// A handler for failed internal MDBX assertions, see [Environment::set_assert_handler()].
+///
+/// Receives the assertion message, the function it failed in and the line number.
+pub type AssertFn = fn(msg: &str, function: &str, line: u32);
+
+unsafe extern "C" fn assert_trampoline(
+    env: *const ffi::MDBX_env,
+    msg: *const c_char,
+    function: *const c_char,
+    line: c_uint,
+) {
+    let handlers = ffi::mdbx_env_get_userctx(env) as *const Handlers;
+    if handlers.is_null() {
+        return;
+    }
+    let handler = (*handlers).assert.load(Ordering::SeqCst);
+    if handler.is_null() {
+        return;
+    }
+    let handler: AssertFn = mem::transmute(handler);
+    let to_str = |s: *const c_char| {
+        if s.is_null() {
+            Cow::Borrowed("")
+        } else {
+            CStr::from_ptr(s).to_string_lossy()
+        }
+    };
+    let (msg, function) = (to_str(msg), to_str(function));
+    if panic::catch_unwind(|| handler(&msg, &function, line)).is_err() {
+        process::abort();
+    }
+}
+
+/// A handler for slow readers, see [Environment::set_hsr()].
+pub type HsrFn = fn(reader: &SlowReader) -> HsrDecision;
+
+/// The read transaction passed to a [HsrFn], which keeps the write transaction from reusing
+/// pages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlowReader {
+    /// Process running the transaction.
+    pub pid: libc::pid_t,
+    /// Thread running the transaction.
+    pub tid: u64,
+    /// ID of the snapshot the transaction reads.
+    pub txnid: u64,
+    /// Number of transactions committed since that snapshot.
+    pub lag: u32,
+    /// Bytes that would become reusable once the transaction ends.
+    pub space: usize,
+    /// How many times the handler was already called while handling this shortage, starting
+    /// from 0.
+    pub retry: u32,
+}
+
+/// What a [HsrFn] did about a [SlowReader].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HsrDecision {
+    /// Nothing: MDBX grows the datafile if it can, or fails with [Error::MapFull].
+    GiveUp,
+    /// The reader finished or was waited for: MDBX rescans the readers and retries.
+    Retry,
+    /// The read transaction was abandoned and will be aborted or reset later: MDBX clears its
+    /// reader slot and retries.
+    ReaderAborted,
+    /// The reading thread or process was killed: MDBX releases its reader slot entirely and
+    /// retries.
+    ReaderKilled,
+}
+
+impl HsrDecision {
+    /// The return code MDBX expects from the callback.
+    fn code(self) -> c_int {
+        match self {
+            HsrDecision::GiveUp => -1,
+            HsrDecision::Retry => 0,
+            HsrDecision::ReaderAborted => 1,
+            HsrDecision::ReaderKilled => 2,
+        }
+    }
+}
+
+// `mdbx_tid_t` is only 32 bits on some targets.
+#[allow(clippy::too_many_arguments, clippy::unnecessary_cast)]
+unsafe extern "C" fn hsr_trampoline(
+    env: *const ffi::MDBX_env,
+    _txn: *const ffi::MDBX_txn,
+    pid: ffi::mdbx_pid_t,
+    tid: ffi::mdbx_tid_t,
+    laggard: u64,
+    gap: c_uint,
+    space: usize,
+    retry: c_int,
+) -> c_int {
+    // A negative retry only notifies the end of the handling loop.
+    if retry < 0 {
+        return 0;
+    }
+    let handlers = ffi::mdbx_env_get_userctx(env) as *const Handlers;
+    if handlers.is_null() {
+        return HsrDecision::GiveUp.code();
+    }
+    let handler = (*handlers).hsr.load(Ordering::SeqCst);
+    if handler.is_null() {
+        return HsrDecision::GiveUp.code();
+    }
+    let handler: HsrFn = mem::transmute(handler);
+    let reader = SlowReader {
+        pid,
+        tid: tid as u64,
+        txnid: laggard,
+        lag: gap,
+        space,
+        retry: retry as u32,
+    };
+    panic::catch_unwind(|| handler(&reader))
+        .unwrap_or(HsrDecision::GiveUp)
+        .code()
 }
 
 unsafe impl<E> Send for Environment<E> where E: EnvironmentKind {}
@@ -351,8 +1136,12 @@ where
     E: EnvironmentKind,
 {
     fn drop(&mut self) {
+        if self.env.is_null() {
+            return;
+        }
+        self.shutdown();
         unsafe {
-            ffi::mdbx_env_close_ex(self.env, false);
+            ffi::mdbx_env_close_ex(self.env, !self.sync_on_drop);
         }
     }
 }
@@ -402,6 +1191,10 @@ where
     spill_max_denominator: Option<u64>,
     spill_min_denominator: Option<u64>,
     geometry: Option<Geometry<(Option<usize>, Option<usize>)>>,
+    page_size: Option<usize>,
+    ro_txn_pool_size: usize,
+    sync_on_drop: bool,
+    close_dbs: bool,
     _marker: PhantomData<E>,
 }
 
@@ -419,6 +1212,23 @@ where
         self.open_with_permissions(path, 0o644)
     }
 
+    /// Open an environment that must already exist, instead of creating a new empty one.
+    ///
+    /// Fails with [Error::Other] holding `ENOENT` if there is no data file at `path`, which is
+    /// `mdbx.dat` inside the directory, or `path` itself with
+    /// [EnvironmentFlags::no_sub_dir]. Otherwise this is the same as [EnvironmentBuilder::open()].
+    pub fn open_existing(&self, path: &Path) -> Result<Environment<E>> {
+        let data_path = if self.flags.no_sub_dir {
+            path.to_path_buf()
+        } else {
+            path.join("mdbx.dat")
+        };
+        if !data_path.is_file() {
+            return Err(Error::Other(libc::ENOENT));
+        }
+        self.open(path)
+    }
+
     /// Open an environment with the provided UNIX permissions.
     ///
     /// The path may not contain the null character, Windows UNC (Uniform Naming Convention)
@@ -432,7 +1242,23 @@ where
         unsafe {
             mdbx_result(ffi::mdbx_env_create(&mut env))?;
             if let Err(e) = (|| {
-                if let Some(geometry) = &self.geometry {
+                if let Some(page_size) = self.page_size {
+                    if !page_size.is_power_of_two()
+                        || page_size < ffi::MDBX_MIN_PAGESIZE as usize
+                        || page_size > ffi::MDBX_MAX_PAGESIZE as usize
+                    {
+                        return Err(Error::InvalidPageSize(page_size));
+                    }
+                }
+
+                if let Some(max_dbs) = self.max_dbs {
+                    if max_dbs > ffi::MDBX_MAX_DBI as u64 {
+                        return Err(Error::TooManyDbs(max_dbs as usize));
+                    }
+                }
+
+                if self.geometry.is_some() || self.page_size.is_some() {
+                    let geometry = self.geometry.clone().unwrap_or_default();
                     let mut min_size = -1;
                     let mut max_size = -1;
 
@@ -453,7 +1279,7 @@ where
                         max_size,
                         geometry.growth_step.unwrap_or(-1),
                         geometry.shrink_threshold.unwrap_or(-1),
-                        match geometry.page_size {
+                        match self.page_size.map(PageSize::Set).or(geometry.page_size) {
                             None => -1,
                             Some(PageSize::MinimalAcceptable) => 0,
                             Some(PageSize::Set(size)) => size as isize,
@@ -462,6 +1288,7 @@ where
                 }
                 for (opt, v) in [
                     (ffi::MDBX_opt_max_db, self.max_dbs),
+                    (ffi::MDBX_opt_max_readers, self.max_readers.map(u64::from)),
                     (ffi::MDBX_opt_rp_augment_limit, self.rp_augment_limit),
                     (ffi::MDBX_opt_loose_limit, self.loose_limit),
                     (ffi::MDBX_opt_dp_reserve_limit, self.dp_reserve_limit),
@@ -502,13 +1329,21 @@ where
         let mut env = Environment {
             env,
             txn_manager: None,
+            txn_manager_thread: None,
+            ro_txn_pool: Mutex::new(Vec::with_capacity(self.ro_txn_pool_size)),
+            ro_txn_pool_size: self.ro_txn_pool_size,
+            read_only: matches!(self.flags.mode, Mode::ReadOnly),
+            sync_on_drop: self.sync_on_drop,
+            close_dbs: self.close_dbs,
+            handlers: Box::default(),
+            dbi_refs: Mutex::default(),
             _marker: PhantomData,
         };
 
         if let Mode::ReadWrite { .. } = self.flags.mode {
             let (tx, rx) = std::sync::mpsc::sync_channel(0);
             let e = EnvPtr(env.env);
-            std::thread::spawn(move || loop {
+            let handle = std::thread::spawn(move || loop {
                 match rx.recv() {
                     Ok(msg) => match msg {
                         TxnManagerMessage::Begin {
@@ -530,19 +1365,41 @@ where
                                     })
                                     .map(|_| TxnPtr(txn)),
                                 )
-                                .unwrap()
+                                .ok();
                         }
                         TxnManagerMessage::Abort { tx, sender } => {
                             sender
                                 .send(mdbx_result(unsafe { ffi::mdbx_txn_abort(tx.0) }))
-                                .unwrap();
+                                .ok();
                         }
                         TxnManagerMessage::Commit { tx, sender } => {
                             sender
                                 .send(mdbx_result(unsafe {
                                     ffi::mdbx_txn_commit_ex(tx.0, ptr::null_mut())
                                 }))
-                                .unwrap();
+                                .ok();
+                        }
+                        TxnManagerMessage::CommitAndBegin { tx, flags, sender } => {
+                            let mut txn: *mut ffi::MDBX_txn = ptr::null_mut();
+                            sender
+                                .send(
+                                    mdbx_result(unsafe {
+                                        ffi::mdbx_txn_commit_ex(tx.0, ptr::null_mut())
+                                    })
+                                    .map(|v| {
+                                        let begin = mdbx_result(unsafe {
+                                            ffi::mdbx_txn_begin_ex(
+                                                e.0,
+                                                ptr::null_mut(),
+                                                flags,
+                                                &mut txn,
+                                                ptr::null_mut(),
+                                            )
+                                        });
+                                        (v, begin.map(|_| TxnPtr(txn)))
+                                    }),
+                                )
+                                .ok();
                         }
                     },
                     Err(_) => return,
@@ -550,6 +1407,7 @@ where
             });
 
             env.txn_manager = Some(tx);
+            env.txn_manager_thread = Some(handle);
         }
 
         Ok(env)
@@ -561,11 +1419,57 @@ where
         self
     }
 
+    /// Sets the durability of a read-write environment, leaving the other options alone. Each
+    /// [SyncMode] sets exactly the MDBX flags it needs, so they can't be combined incorrectly.
+    ///
+    /// Has no effect if the builder is set to [Mode::ReadOnly], which stays read-only.
+    pub fn set_sync_mode(&mut self, sync_mode: SyncMode) -> &mut Self {
+        if let Mode::ReadWrite { sync_mode: mode } = &mut self.flags.mode {
+            *mode = sync_mode;
+        }
+        self
+    }
+
+    /// Treats the path passed to [EnvironmentBuilder::open()] as the data file itself, rather
+    /// than as a directory holding `mdbx.dat` and `mdbx.lck`. The lock file is then created next
+    /// to it, with `-lck` appended to its name. See [EnvironmentFlags::no_sub_dir].
+    pub fn set_no_sub_dir(&mut self, no_sub_dir: bool) -> &mut Self {
+        self.flags.no_sub_dir = no_sub_dir;
+        self
+    }
+
+    /// Opens the environment for use by this process only, see [EnvironmentFlags::exclusive].
+    ///
+    /// libmdbx 0.11 still creates the lock file in exclusive mode, so this doesn't avoid it.
+    pub fn set_exclusive(&mut self, exclusive: bool) -> &mut Self {
+        self.flags.exclusive = exclusive;
+        self
+    }
+
+    /// Reuses the most recently freed pages first, see [EnvironmentFlags::liforeclaim].
+    pub fn set_lifo_reclaim(&mut self, lifo_reclaim: bool) -> &mut Self {
+        self.flags.liforeclaim = lifo_reclaim;
+        self
+    }
+
+    /// Merges adjacent garbage collector records, see [EnvironmentFlags::coalesce].
+    pub fn set_coalesce(&mut self, coalesce: bool) -> &mut Self {
+        self.flags.coalesce = coalesce;
+        self
+    }
+
     /// Sets the maximum number of threads or reader slots for the environment.
     ///
     /// This defines the number of slots in the lock table that is used to track readers in the
     /// the environment. The default is 126. Starting a read-only transaction normally ties a lock
     /// table slot to the [Transaction] object until it or the [Environment] object is destroyed.
+    ///
+    /// Starting a read-only transaction when all slots are in use fails with
+    /// [Error::ReadersFull].
+    ///
+    /// Environments are always opened with `MDBX_NOTLS`, so slots belong to transactions rather
+    /// than threads: a slot is released as soon as its transaction ends, and threads in a pool can
+    /// come and go without registering or unregistering themselves.
     pub fn set_max_readers(&mut self, max_readers: c_uint) -> &mut Self {
         self.max_readers = Some(max_readers);
         self
@@ -580,6 +1484,9 @@ where
     /// Currently a moderate number of slots are cheap but a huge number gets
     /// expensive: 7-120 words per transaction, and every [Transaction::open_db()]
     /// does a linear search of the opened slots.
+    ///
+    /// MDBX supports at most 32765 named databases; opening the environment with more fails with
+    /// [Error::TooManyDbs].
     pub fn set_max_dbs(&mut self, v: usize) -> &mut Self {
         self.max_dbs = Some(v as u64);
         self
@@ -615,6 +1522,53 @@ where
         self
     }
 
+    /// Sets the maximum number of idle read-only transactions kept for reuse by
+    /// [Environment::ro_txn()]. The default is 0, which disables pooling.
+    ///
+    /// Each pooled transaction holds on to a reader slot, see [Self::set_max_readers()].
+    pub fn set_ro_txn_pool_size(&mut self, v: usize) -> &mut Self {
+        self.ro_txn_pool_size = v;
+        self
+    }
+
+    /// Sets whether data buffers are flushed to disk when the environment is closed or dropped.
+    /// The default is true.
+    ///
+    /// This matters with [SyncMode::SafeNoSync](crate::SyncMode::SafeNoSync) and
+    /// [SyncMode::UtterlyNoSync](crate::SyncMode::UtterlyNoSync), where commits are not flushed
+    /// by themselves: with syncing enabled they become durable at shutdown at the latest.
+    /// MDBX skips the flush rather than block if another process is writing at that moment.
+    /// Disable it for faster shutdown when the data doesn't need to survive a system crash.
+    pub fn set_sync_on_drop(&mut self, v: bool) -> &mut Self {
+        self.sync_on_drop = v;
+        self
+    }
+
+    /// Sets whether database handles are closed once every transaction that opened them has
+    /// ended, unless they were opened with [Environment::open_db_permanent()] or kept with
+    /// [Transaction::commit_and_rebind_open_dbs()]. The default is false, so handles stay open
+    /// for the lifetime of the environment.
+    ///
+    /// This lets an environment open many different named databases over time without running
+    /// into the limit set with [EnvironmentBuilder::set_max_dbs()]. The cost is that a database
+    /// opened in every transaction is looked up by name again each time, and that opening
+    /// database handles is serialized across threads.
+    pub fn set_close_dbs(&mut self, v: bool) -> &mut Self {
+        self.close_dbs = v;
+        self
+    }
+
+    /// Sets the database page size, which must be a power of two between
+    /// [ffi::MDBX_MIN_PAGESIZE] and [ffi::MDBX_MAX_PAGESIZE]. Opening the environment fails with
+    /// [Error::InvalidPageSize] otherwise.
+    ///
+    /// The page size only takes effect when the environment is created, and overrides the page
+    /// size given to [Self::set_geometry()].
+    pub fn set_page_size(&mut self, page_size: usize) -> &mut Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
     /// Set all size-related parameters of environment, including page size and the min/max size of the memory map.
     pub fn set_geometry<R: RangeBounds<usize>>(&mut self, geometry: Geometry<R>) -> &mut Self {
         let convert_bound = |bound: Bound<&usize>| match bound {
@@ -689,6 +1643,197 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_page_size() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new()
+            .set_page_size(8192)
+            .open(dir.path())
+            .unwrap();
+        assert_eq!(env.page_size().unwrap(), 8192);
+
+        for page_size in [3000, 128, 131072] {
+            let dir = tempdir().unwrap();
+            assert!(matches!(
+                Environment::new().set_page_size(page_size).open(dir.path()),
+                Err(Error::InvalidPageSize(size)) if size == page_size
+            ));
+        }
+    }
+
+    #[test]
+    fn test_latest_txn_id() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let initial = env.latest_txn_id().unwrap();
+        assert_eq!(initial, env.begin_ro_txn().unwrap().id());
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let latest = env.latest_txn_id().unwrap();
+        assert!(latest > initial);
+        assert_eq!(latest, env.begin_ro_txn().unwrap().id());
+    }
+
+    #[test]
+    fn test_write_map() {
+        let dir = tempdir().unwrap();
+        let env = crate::Environment::<WriteMap>::new()
+            .open(dir.path())
+            .unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val"));
+    }
+
+    #[test]
+    fn test_txn_manager_dead() {
+        let dir = tempdir().unwrap();
+        let mut env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        txn.commit().unwrap();
+
+        // Swap in a channel nobody is listening on, as if the manager thread had died.
+        let (tx, rx) = sync_channel(0);
+        drop(rx);
+        env.txn_manager = Some(tx);
+
+        assert!(matches!(env.begin_rw_txn(), Err(Error::TxnManagerDead)));
+        assert!(matches!(env.begin_rw_txn_try(), Err(Error::TxnManagerDead)));
+
+        // Read-only transactions don't involve the manager.
+        env.begin_ro_txn().unwrap();
+    }
+
+    #[test]
+    fn test_txn_manager_dead_on_commit() {
+        let dir = tempdir().unwrap();
+        let mut env = Environment::new().open(dir.path()).unwrap();
+
+        // Put a proxy in front of the manager that commits but never delivers the reply, as if the
+        // manager thread had died while handling the request.
+        let manager = env.txn_manager.take().unwrap();
+        let (tx, rx) = sync_channel(0);
+        let proxy = std::thread::spawn(move || {
+            while let Ok(msg) = rx.recv() {
+                match msg {
+                    TxnManagerMessage::Commit { tx, sender } => {
+                        let (reply, result) = sync_channel(0);
+                        manager
+                            .send(TxnManagerMessage::Commit { tx, sender: reply })
+                            .unwrap();
+                        result.recv().unwrap().unwrap();
+                        drop(sender);
+                    }
+                    msg => manager.send(msg).unwrap(),
+                }
+            }
+        });
+        env.txn_manager = Some(tx);
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+        assert!(matches!(txn.commit(), Err(Error::TxnManagerDead)));
+
+        env.txn_manager = None;
+        proxy.join().unwrap();
+    }
+
+    #[test]
+    fn test_close() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new()
+            .set_ro_txn_pool_size(1)
+            .open(dir.path())
+            .unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+        drop(env.ro_txn().unwrap());
+
+        env.close().unwrap();
+
+        let env = Environment::new()
+            .set_flags(Mode::ReadOnly.into())
+            .open(dir.path())
+            .unwrap();
+        {
+            let txn = env.begin_ro_txn().unwrap();
+            let db = txn.open_db(None).unwrap();
+            assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val"));
+        }
+        env.close().unwrap();
+    }
+
+    #[test]
+    fn test_ro_txn_pool() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new()
+            .set_ro_txn_pool_size(1)
+            .open(dir.path())
+            .unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"val1", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let raw = {
+            let txn = env.ro_txn().unwrap();
+            let db = txn.open_db(None).unwrap();
+            assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val1"));
+            txn.txn()
+        };
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"val2", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.ro_txn().unwrap();
+        assert_eq!(txn.txn(), raw);
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val2"));
+
+        // The pool is empty while the first transaction is out, so this one is fresh.
+        let other = env.ro_txn().unwrap();
+        assert_ne!(other.txn(), raw);
+        assert_eq!(other.id(), txn.id());
+    }
+
+    #[test]
+    fn test_begin_rw_txn_try() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn_try().unwrap().unwrap();
+        assert!(env.begin_rw_txn_try().unwrap().is_none());
+        drop(txn);
+
+        assert!(env.begin_rw_txn_try().unwrap().is_some());
+        drop(env);
+
+        let env = Environment::new()
+            .set_flags(Mode::ReadOnly.into())
+            .open(dir.path())
+            .unwrap();
+        assert!(matches!(env.begin_rw_txn_try().unwrap_err(), Error::Access));
+    }
+
     #[test]
     fn test_open_db() {
         let dir = tempdir().unwrap();
@@ -722,6 +1867,624 @@ mod test {
         txn.open_db(Some("db")).unwrap();
     }
 
+    #[test]
+    fn test_with_raw_env() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(7).open(dir.path()).unwrap();
+
+        let mut max_dbs = 0;
+        let rc = unsafe {
+            env.with_raw_env(|env| {
+                ffi::mdbx_env_get_option(env, ffi::MDBX_opt_max_db, &mut max_dbs)
+            })
+        };
+        mdbx_result(rc).unwrap();
+        assert_eq!(max_dbs, 7);
+    }
+
+    #[test]
+    fn test_userctx() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+        assert!(env.userctx::<String>().is_none());
+
+        let ctx = Arc::new(String::from("app state"));
+        env.set_userctx(ctx.clone());
+        assert_eq!(*env.userctx::<String>().unwrap(), "app state");
+        assert!(Arc::ptr_eq(&env.userctx::<String>().unwrap(), &ctx));
+        assert!(env.userctx::<u64>().is_none());
+
+        env.set_userctx(Arc::new(7u64));
+        assert_eq!(*env.userctx::<u64>().unwrap(), 7);
+        assert!(env.userctx::<String>().is_none());
+        assert_eq!(Arc::strong_count(&ctx), 1);
+
+        env.set_userctx(ctx.clone());
+        drop(env);
+        assert_eq!(Arc::strong_count(&ctx), 1);
+    }
+
+    #[test]
+    fn test_reader_slots_not_tied_to_threads() {
+        let dir = tempdir().unwrap();
+        let env = std::sync::Arc::new(
+            Environment::new()
+                .set_max_readers(8)
+                .open(dir.path())
+                .unwrap(),
+        );
+        let txn = env.begin_rw_txn().unwrap();
+        txn.put(
+            &txn.open_db(None).unwrap(),
+            b"key",
+            b"val",
+            WriteFlags::empty(),
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        // Many more short-lived threads than there are slots.
+        for _ in 0..4 * env.max_readers().unwrap() {
+            let env = env.clone();
+            std::thread::spawn(move || {
+                let txn = env.begin_ro_txn().unwrap();
+                let db = txn.open_db(None).unwrap();
+                assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val"));
+            })
+            .join()
+            .unwrap();
+        }
+        // The same slot was reused by every thread.
+        assert_eq!(env.info().unwrap().num_readers(), 1);
+    }
+
+    #[test]
+    fn test_open_existing() {
+        let dir = tempdir().unwrap();
+
+        let missing = dir.path().join("missing");
+        assert!(matches!(
+            Environment::new().open_existing(&missing),
+            Err(Error::Other(libc::ENOENT))
+        ));
+        assert!(!missing.exists());
+
+        // An existing directory without a data file is not an environment.
+        assert!(matches!(
+            Environment::new().open_existing(dir.path()),
+            Err(Error::Other(libc::ENOENT))
+        ));
+        assert!(!dir.path().join("mdbx.dat").exists());
+
+        drop(Environment::new().open(dir.path()).unwrap());
+        Environment::new().open_existing(dir.path()).unwrap();
+
+        let mut builder = Environment::new();
+        builder.set_flags(EnvironmentFlags {
+            no_sub_dir: true,
+            ..Default::default()
+        });
+        let file = dir.path().join("file.mdbx");
+        assert!(matches!(
+            builder.open_existing(&file),
+            Err(Error::Other(libc::ENOENT))
+        ));
+        drop(builder.open(&file).unwrap());
+        builder.open_existing(&file).unwrap();
+    }
+
+    #[test]
+    fn test_no_sub_dir() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("foo.mdbx");
+
+        let env = Environment::new().set_no_sub_dir(true).open(&file).unwrap();
+        let txn = env.begin_rw_txn().unwrap();
+        txn.put(
+            &txn.open_db(None).unwrap(),
+            b"key",
+            b"val",
+            WriteFlags::empty(),
+        )
+        .unwrap();
+        txn.commit().unwrap();
+        drop(env);
+
+        assert!(file.is_file());
+        assert!(dir.path().join("foo.mdbx-lck").is_file());
+        assert!(!dir.path().join("mdbx.dat").exists());
+
+        let env = Environment::new().set_no_sub_dir(true).open(&file).unwrap();
+        let txn = env.begin_ro_txn().unwrap();
+        assert_eq!(
+            txn.get(&txn.open_db(None).unwrap(), b"key").unwrap(),
+            Some(*b"val")
+        );
+    }
+
+    #[test]
+    fn test_open_invalid() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("mdbx.dat"), vec![0xa5; 64 * 1024]).unwrap();
+        assert!(matches!(
+            Environment::new().open(dir.path()),
+            Err(Error::Invalid)
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let dups = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        for i in 0..2000u32 {
+            txn.put(&db, i.to_be_bytes(), [0u8; 100], WriteFlags::empty())
+                .unwrap();
+            txn.put(
+                &dups,
+                (i % 10).to_be_bytes(),
+                i.to_be_bytes(),
+                WriteFlags::empty(),
+            )
+            .unwrap();
+        }
+        txn.put(&db, b"large", [0u8; 20_000], WriteFlags::empty())
+            .unwrap();
+        txn.commit().unwrap();
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for i in 0..1000u32 {
+            txn.del(&db, i.to_be_bytes(), None).unwrap();
+        }
+        txn.commit().unwrap();
+
+        let report = env.validate().unwrap();
+        assert!(report.is_ok(), "{:?}", report.issues);
+        // Every allocated page is either in a tree or in the GC.
+        assert_eq!(
+            report.pages + env.freelist_pages().unwrap(),
+            env.info().unwrap().last_pgno() as u64 + 1
+        );
+    }
+
+    #[test]
+    fn test_open_db_permanent() {
+        let dir = tempdir().unwrap();
+        {
+            let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+            let db = env
+                .open_db_permanent(Some("dups"), DatabaseFlags::DUP_SORT)
+                .unwrap();
+
+            for value in [b"1", b"2"] {
+                let txn = env.begin_rw_txn().unwrap();
+                txn.put(&db, b"key", value, WriteFlags::empty()).unwrap();
+                txn.commit().unwrap();
+            }
+
+            let txn = env.begin_ro_txn().unwrap();
+            assert_eq!(txn.db_flags(&db).unwrap(), DatabaseFlags::DUP_SORT);
+            assert_eq!(txn.db_stat(&db).unwrap().entries(), 2);
+        }
+
+        let env = Environment::new()
+            .set_max_dbs(2)
+            .set_flags(Mode::ReadOnly.into())
+            .open(dir.path())
+            .unwrap();
+        let db = env
+            .open_db_permanent(Some("dups"), DatabaseFlags::empty())
+            .unwrap();
+        let txn = env.begin_ro_txn().unwrap();
+        assert_eq!(txn.db_stat(&db).unwrap().entries(), 2);
+        assert!(env
+            .open_db_permanent(Some("missing"), DatabaseFlags::empty())
+            .is_err());
+    }
+
+    #[test]
+    fn test_assert_handler() {
+        static CALLS: std::sync::Mutex<Vec<(String, String, u32)>> =
+            std::sync::Mutex::new(Vec::new());
+        fn handler(msg: &str, function: &str, line: u32) {
+            CALLS
+                .lock()
+                .unwrap()
+                .push((msg.to_string(), function.to_string(), line));
+        }
+
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+        match env.set_assert_handler(Some(handler)) {
+            Ok(()) => (),
+            Err(Error::Other(code)) => assert_eq!(code, ffi::MDBX_ENOSYS),
+            Err(e) => panic!("{}", e),
+        }
+
+        // Assertions can't be triggered on purpose, so exercise the trampoline directly.
+        let msg = CString::new("x > 0").unwrap();
+        let function = CString::new("some_fn").unwrap();
+        unsafe {
+            env.handlers
+                .assert
+                .store(handler as *mut (), Ordering::SeqCst);
+            assert_trampoline(env.env(), msg.as_ptr(), function.as_ptr(), 42);
+            assert_trampoline(env.env(), msg.as_ptr(), ptr::null(), 7);
+        }
+        assert_eq!(
+            *CALLS.lock().unwrap(),
+            vec![
+                ("x > 0".to_string(), "some_fn".to_string(), 42),
+                ("x > 0".to_string(), String::new(), 7)
+            ]
+        );
+
+        env.set_assert_handler(None).unwrap();
+        assert!(env.handlers.assert.load(Ordering::SeqCst).is_null());
+    }
+
+    #[test]
+    fn test_resize_map() {
+        fn open(path: &Path) -> Environment {
+            Environment::new()
+                .set_geometry(Geometry {
+                    size: Some(0..1024 * 1024),
+                    ..Default::default()
+                })
+                .open(path)
+                .unwrap()
+        }
+
+        fn write(env: &Environment, i: u32) -> Result<()> {
+            let txn = env.begin_rw_txn()?;
+            let db = txn.open_db(None)?;
+            txn.put(&db, i.to_be_bytes(), [0; 1024], WriteFlags::empty())?;
+            txn.commit().map(|_| ())
+        }
+
+        let dir = tempdir().unwrap();
+        let mut env = open(dir.path());
+        env.resize_map(256 * 1024).unwrap();
+        assert_eq!(env.info().unwrap().geometry().max(), 256 * 1024);
+
+        let full = (0..1000).find(|&i| write(&env, i).is_err()).unwrap();
+        assert!(matches!(write(&env, full), Err(Error::MapFull)));
+        assert!(matches!(env.resize_map(64 * 1024), Err(Error::MapFull)));
+
+        match env.resize_map(1024 * 1024) {
+            Ok(()) => {}
+            // The addresses freed by shrinking were taken by another mapping in the meantime.
+            Err(Error::UnableExtendMapsize) => {
+                drop(env);
+                env = open(dir.path());
+            }
+            Err(e) => panic!("{}", e),
+        }
+        assert_eq!(env.info().unwrap().geometry().max(), 1024 * 1024);
+        for i in full..full + 100 {
+            write(&env, i).unwrap();
+        }
+        let txn = env.begin_ro_txn().unwrap();
+        let stat = txn.db_stat(&txn.open_db(None).unwrap()).unwrap();
+        assert_eq!(stat.entries(), full as usize + 100);
+    }
+
+    #[test]
+    fn test_hsr() {
+        static READERS: std::sync::Mutex<Vec<SlowReader>> = std::sync::Mutex::new(Vec::new());
+        fn handler(reader: &SlowReader) -> HsrDecision {
+            READERS.lock().unwrap().push(*reader);
+            if reader.retry == 0 {
+                HsrDecision::Retry
+            } else {
+                HsrDecision::GiveUp
+            }
+        }
+
+        assert_eq!(HsrDecision::GiveUp.code(), -1);
+        assert_eq!(HsrDecision::Retry.code(), 0);
+        assert_eq!(HsrDecision::ReaderAborted.code(), 1);
+        assert_eq!(HsrDecision::ReaderKilled.code(), 2);
+
+        let dir = tempdir().unwrap();
+        let env = Environment::new()
+            .set_geometry(Geometry {
+                size: Some(0..256 * 1024),
+                ..Default::default()
+            })
+            .open(dir.path())
+            .unwrap();
+        env.set_hsr(Some(handler)).unwrap();
+
+        // Keep an old snapshot alive while rewriting the database until it fills up.
+        let txn = env.begin_rw_txn().unwrap();
+        txn.put(
+            &txn.open_db(None).unwrap(),
+            b"key",
+            [0; 1024],
+            WriteFlags::empty(),
+        )
+        .unwrap();
+        txn.commit().unwrap();
+        let reader = env.begin_ro_txn().unwrap();
+        let res = (0..1000u32).try_for_each(|i| {
+            let txn = env.begin_rw_txn()?;
+            let db = txn.open_db(None)?;
+            txn.put(&db, b"key", [0; 1024], WriteFlags::empty())?;
+            txn.put(&db, i.to_be_bytes(), [0; 1024], WriteFlags::empty())?;
+            txn.commit().map(|_| ())
+        });
+        assert!(matches!(res, Err(Error::MapFull)));
+
+        let readers = READERS.lock().unwrap();
+        // Each failed page allocation asks once to retry, then gives up.
+        assert!(!readers.is_empty());
+        for (i, slow) in readers.iter().enumerate() {
+            assert_eq!(slow.pid, std::process::id() as libc::pid_t);
+            assert_eq!(slow.txnid, reader.id());
+            assert!(slow.lag > 0);
+            assert_eq!(slow.retry as usize, i % 2);
+        }
+
+        // Panics give up.
+        let code = unsafe {
+            env.handlers.hsr.store(
+                (|_: &SlowReader| -> HsrDecision { panic!() }) as HsrFn as *mut (),
+                Ordering::SeqCst,
+            );
+            hsr_trampoline(env.env(), ptr::null(), 1, 1, 1, 1, 1, 0)
+        };
+        assert_eq!(code, HsrDecision::GiveUp.code());
+
+        env.set_hsr(None).unwrap();
+        assert!(env.handlers.hsr.load(Ordering::SeqCst).is_null());
+    }
+
+    #[test]
+    fn test_set_sync_mode() {
+        let sync_bits = ffi::MDBX_NOMETASYNC | ffi::MDBX_SAFE_NOSYNC | ffi::MDBX_UTTERLY_NOSYNC;
+        for (sync_mode, expected) in [
+            (SyncMode::Durable, ffi::MDBX_SYNC_DURABLE),
+            (SyncMode::NoMetaSync, ffi::MDBX_NOMETASYNC),
+            // MDBX implies `MDBX_NOMETASYNC` for the no-sync modes.
+            (
+                SyncMode::SafeNoSync,
+                ffi::MDBX_SAFE_NOSYNC | ffi::MDBX_NOMETASYNC,
+            ),
+            (
+                SyncMode::UtterlyNoSync,
+                ffi::MDBX_UTTERLY_NOSYNC | ffi::MDBX_NOMETASYNC,
+            ),
+        ] {
+            let dir = tempdir().unwrap();
+            let env = Environment::new()
+                .set_sync_mode(sync_mode)
+                .open(dir.path())
+                .unwrap();
+            assert!(!env.is_read_only());
+
+            let mut flags = 0;
+            mdbx_result(unsafe { ffi::mdbx_env_get_flags(env.env(), &mut flags) }).unwrap();
+            assert_eq!(flags & sync_bits, expected, "{:?}", sync_mode);
+            drop(env);
+
+            // A read-only builder stays read-only.
+            let env = Environment::new()
+                .set_flags(Mode::ReadOnly.into())
+                .set_sync_mode(sync_mode)
+                .open(dir.path())
+                .unwrap();
+            assert!(env.is_read_only());
+            assert!(matches!(env.begin_rw_txn(), Err(Error::Access)));
+        }
+    }
+
+    #[test]
+    fn test_reclaim_flags() {
+        for (lifo_reclaim, coalesce) in [(false, false), (true, false), (false, true), (true, true)]
+        {
+            let dir = tempdir().unwrap();
+            let env = Environment::new()
+                .set_lifo_reclaim(lifo_reclaim)
+                .set_coalesce(coalesce)
+                .open(dir.path())
+                .unwrap();
+
+            let mut flags = 0;
+            mdbx_result(unsafe { ffi::mdbx_env_get_flags(env.env(), &mut flags) }).unwrap();
+            assert_eq!(flags & ffi::MDBX_LIFORECLAIM != 0, lifo_reclaim);
+            assert_eq!(flags & ffi::MDBX_COALESCE != 0, coalesce);
+        }
+    }
+
+    #[test]
+    fn test_begin_rw_txn_timeout() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let start = Instant::now();
+        assert!(env
+            .begin_rw_txn_timeout(Duration::from_millis(100))
+            .unwrap()
+            .is_none());
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        // Succeeds once the other writer commits within the timeout.
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                sleep(Duration::from_millis(100));
+                txn.commit().unwrap();
+            });
+            let txn = env
+                .begin_rw_txn_timeout(Duration::from_secs(10))
+                .unwrap()
+                .unwrap();
+            txn.commit().unwrap();
+        });
+    }
+
+    #[test]
+    fn test_info_geometry() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let info = env.info().unwrap();
+        let geometry = info.geometry();
+        assert!(geometry.min() <= geometry.current());
+        assert!(geometry.current() <= geometry.max());
+        assert!(geometry.growth_step() > 0);
+        assert_eq!(env.info().unwrap().bootid(), info.bootid());
+    }
+
+    #[test]
+    fn test_max_key_value_size() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let max_key = env.max_key_size_default().unwrap();
+        assert_eq!(max_key, env.max_key_size(DatabaseFlags::empty()).unwrap());
+        assert_eq!(
+            env.max_value_size_default().unwrap(),
+            env.max_value_size(DatabaseFlags::empty()).unwrap()
+        );
+        assert!(
+            env.max_value_size(DatabaseFlags::DUP_SORT).unwrap()
+                < env.max_value_size_default().unwrap()
+        );
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, vec![1; max_key], b"val", WriteFlags::empty())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_max_dbs() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(5).open(dir.path()).unwrap();
+        assert_eq!(env.max_dbs().unwrap(), 5);
+
+        let dir = tempdir().unwrap();
+        let err = Environment::new()
+            .set_max_dbs(1 << 20)
+            .open(dir.path())
+            .unwrap_err();
+        assert!(matches!(err, Error::TooManyDbs(max_dbs) if max_dbs == 1 << 20));
+        assert_eq!(
+            err.to_string(),
+            "too many databases 1048576: must be at most 32765"
+        );
+    }
+
+    #[test]
+    fn test_max_readers() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new()
+            .set_max_readers(8)
+            .open(dir.path())
+            .unwrap();
+
+        let max_readers = env.max_readers().unwrap();
+        assert!(max_readers >= 8);
+
+        let readers = (0..max_readers)
+            .map(|_| env.begin_ro_txn().unwrap())
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            env.begin_ro_txn().unwrap_err(),
+            Error::ReadersFull
+        ));
+        drop(readers);
+        drop(env);
+
+        let env = Environment::new()
+            .set_max_readers(max_readers * 2)
+            .open(dir.path())
+            .unwrap();
+        assert!(env.max_readers().unwrap() >= max_readers * 2);
+        let _readers = (0..max_readers + 1)
+            .map(|_| env.begin_ro_txn().unwrap())
+            .collect::<Vec<_>>();
+    }
+
+    #[test]
+    fn test_is_read_only() {
+        let dir = tempdir().unwrap();
+        {
+            let env = Environment::new().open(dir.path()).unwrap();
+            assert!(!env.is_read_only());
+        }
+        {
+            let env = Environment::new()
+                .set_flags(Mode::ReadOnly.into())
+                .open(dir.path())
+                .unwrap();
+            assert!(env.is_read_only());
+            assert!(matches!(env.begin_rw_txn(), Err(Error::Access)));
+        }
+    }
+
+    #[test]
+    fn test_exclusive() {
+        let dir = tempdir().unwrap();
+        let exclusive = || Environment::new().set_exclusive(true).open(dir.path());
+
+        let env = exclusive().unwrap();
+        assert!(dir.path().join("mdbx.lck").exists());
+        assert!(matches!(
+            Environment::new().open(dir.path()),
+            Err(Error::Other(libc::EAGAIN))
+        ));
+        drop(env);
+
+        let _env = Environment::new().open(dir.path()).unwrap();
+        assert!(matches!(exclusive(), Err(Error::Other(libc::EAGAIN))));
+    }
+
+    #[test]
+    fn test_sync_on_drop() {
+        let write = |path: &Path, sync_on_drop| {
+            let env = Environment::new()
+                .set_flags(
+                    Mode::ReadWrite {
+                        sync_mode: SyncMode::UtterlyNoSync,
+                    }
+                    .into(),
+                )
+                .set_sync_on_drop(sync_on_drop)
+                .open(path)
+                .unwrap();
+            let txn = env.begin_rw_txn().unwrap();
+            let db = txn.open_db(None).unwrap();
+            txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+            txn.commit().unwrap();
+        };
+        let open_ro = |path: &Path| {
+            Environment::new()
+                .set_flags(Mode::ReadOnly.into())
+                .open(path)
+        };
+
+        let dir = tempdir().unwrap();
+        write(dir.path(), true);
+        let env = open_ro(dir.path()).unwrap();
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val"));
+
+        // Without a flush the last commit is only weakly persisted, which a read-only
+        // environment can't recover from.
+        let dir = tempdir().unwrap();
+        write(dir.path(), false);
+        assert!(matches!(open_ro(dir.path()), Err(Error::WannaRecovery)));
+    }
+
     #[test]
     fn test_sync() {
         let dir = tempdir().unwrap();
@@ -738,6 +2501,64 @@ mod test {
         }
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_copy_to_fd() {
+        use std::{
+            fs::File,
+            io,
+            os::unix::io::{AsRawFd, FromRawFd},
+            thread,
+        };
+
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for i in 0..100u32 {
+            txn.put(&db, &i.to_be_bytes(), b"val", WriteFlags::empty())
+                .unwrap();
+        }
+        txn.commit().unwrap();
+
+        let check_copy = |path: &Path| {
+            let copy = Environment::new()
+                .set_flags(EnvironmentFlags {
+                    no_sub_dir: true,
+                    ..Default::default()
+                })
+                .open(path)
+                .unwrap();
+            let txn = copy.begin_ro_txn().unwrap();
+            let db = txn.open_db(None).unwrap();
+            assert_eq!(txn.db_stat(&db).unwrap().entries(), 100);
+            assert_eq!(txn.get(&db, &42u32.to_be_bytes()).unwrap(), Some(*b"val"));
+        };
+
+        // Regular file.
+        let out = tempdir().unwrap();
+        let path = out.path().join("file.mdbx");
+        let file = File::create(&path).unwrap();
+        env.copy_to_fd(file.as_raw_fd(), CopyFlags::empty())
+            .unwrap();
+        drop(file);
+        check_copy(&path);
+
+        // Compacting copy through a pipe.
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let (mut reader, writer) =
+            unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+        let path = out.path().join("pipe.mdbx");
+        let mut dest = File::create(&path).unwrap();
+        let handle = thread::spawn(move || io::copy(&mut reader, &mut dest).unwrap());
+        env.copy_to_fd(writer.as_raw_fd(), CopyFlags::COMPACT)
+            .unwrap();
+        drop(writer);
+        handle.join().unwrap();
+        check_copy(&path);
+    }
+
     #[test]
     fn test_stat() {
         let dir = tempdir().unwrap();
@@ -826,4 +2647,134 @@ mod test {
         freelist = env.freelist().unwrap();
         assert!(freelist > 0);
     }
+
+    #[test]
+    fn test_freelist_pages() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+        assert_eq!(env.freelist_pages().unwrap(), 0);
+
+        let value = [0u8; 512];
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for i in 0..1024u32 {
+            txn.put(&db, i.to_be_bytes(), value, WriteFlags::empty())
+                .unwrap();
+        }
+        txn.commit().unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for i in 0..1024u32 {
+            txn.del(&db, i.to_be_bytes(), None).unwrap();
+        }
+        txn.commit().unwrap();
+
+        // The deleted pages are in the GC, and can't outnumber the pages in the file.
+        let freelist = env.freelist_pages().unwrap();
+        let last_pgno = env.info().unwrap().last_pgno() as u64;
+        assert!(freelist > 0);
+        assert!(freelist <= last_pgno + 1);
+        assert_eq!(env.freelist().unwrap() as u64, freelist);
+    }
+
+    #[test]
+    fn test_default_db_txn() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for i in 0..100u32 {
+            txn.put(&db, i.to_be_bytes(), i.to_le_bytes(), WriteFlags::empty())
+                .unwrap();
+        }
+        txn.create_db(Some("named"), DatabaseFlags::empty())
+            .unwrap();
+        txn.commit().unwrap();
+
+        for i in 0..100u32 {
+            let (txn, db) = env.default_db_txn().unwrap();
+            assert_eq!(db.dbi(), txn.open_db(None).unwrap().dbi());
+            assert_eq!(
+                txn.get(&db, &i.to_be_bytes()).unwrap(),
+                Some(i.to_le_bytes())
+            );
+        }
+        // Opening a named database in between doesn't change the handle.
+        let (txn, db) = env.default_db_txn().unwrap();
+        txn.open_db(Some("named")).unwrap();
+        assert_eq!(db.dbi(), txn.open_db(None).unwrap().dbi());
+        assert_eq!(txn.db_stat(&db).unwrap().entries(), 101);
+    }
+
+    #[test]
+    fn test_sync_nonblocking() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new()
+            .set_sync_mode(SyncMode::SafeNoSync)
+            .open(dir.path())
+            .unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        txn.put(
+            &txn.open_db(None).unwrap(),
+            b"key",
+            b"val",
+            WriteFlags::empty(),
+        )
+        .unwrap();
+        txn.commit().unwrap();
+        assert!(env.sync_nonblocking().unwrap());
+        assert!(!env.sync_nonblocking().unwrap());
+
+        // A writer holds the lock needed to sync.
+        let txn = env.begin_rw_txn().unwrap();
+        txn.put(
+            &txn.open_db(None).unwrap(),
+            b"key",
+            b"val2",
+            WriteFlags::empty(),
+        )
+        .unwrap();
+        assert!(!env.sync_nonblocking().unwrap());
+        txn.commit().unwrap();
+        assert!(env.sync_nonblocking().unwrap());
+    }
+
+    #[test]
+    fn test_begin_ro_txn_with_id() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        txn.put(
+            &txn.open_db(None).unwrap(),
+            b"key1",
+            b"val1",
+            WriteFlags::empty(),
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        let (reader, id) = env.begin_ro_txn_with_id().unwrap();
+        assert_eq!(id, env.latest_txn_id().unwrap());
+
+        let txn = env.begin_rw_txn().unwrap();
+        txn.put(
+            &txn.open_db(None).unwrap(),
+            b"key2",
+            b"val2",
+            WriteFlags::empty(),
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        // The reader stays pinned to its snapshot.
+        assert!(env.latest_txn_id().unwrap() > id);
+        assert_eq!(reader.id(), id);
+        let db = reader.open_db(None).unwrap();
+        assert_eq!(reader.get(&db, b"key1").unwrap(), Some(*b"val1"));
+        assert_eq!(reader.get::<()>(&db, b"key2").unwrap(), None);
+    }
 }
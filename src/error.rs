@@ -1,5 +1,5 @@
 use libc::c_int;
-use std::{ffi::CStr, fmt, result, str};
+use std::{ffi::CStr, fmt, io, result, str};
 
 /// An MDBX error kind.
 #[derive(Debug)]
@@ -9,7 +9,10 @@ pub enum Error {
     PageNotFound,
     Corrupted,
     Panic,
+    /// The database was written by a version of MDBX with an incompatible data format, so it
+    /// must be opened with that version, or copied into a new database with it.
     VersionMismatch,
+    /// The file is not an MDBX database, e.g. it is empty or was written by another program.
     Invalid,
     MapFull,
     DbsFull,
@@ -26,11 +29,32 @@ pub enum Error {
     Problem,
     Busy,
     Multival,
+    /// The database was not closed cleanly and needs to be rolled back to its last durable
+    /// state, which MDBX can't do while it is opened read-only. Opening it read-write once
+    /// recovers it.
     WannaRecovery,
     KeyMismatch,
     InvalidValue,
     Access,
     TooLarge,
+    /// An item passed to [Transaction::bulk_load()](crate::Transaction::bulk_load) did not sort
+    /// after the previous one. Holds the index of the offending item.
+    OutOfOrder(usize),
+    /// The page size passed to
+    /// [EnvironmentBuilder::set_page_size()](crate::EnvironmentBuilder::set_page_size) is not a
+    /// power of two within the range supported by MDBX.
+    InvalidPageSize(usize),
+    /// The number of databases passed to
+    /// [EnvironmentBuilder::set_max_dbs()](crate::EnvironmentBuilder::set_max_dbs) is more than
+    /// MDBX supports.
+    TooManyDbs(usize),
+    /// The background thread that begins and ends read-write transactions has stopped, so no
+    /// further read-write transactions can be used with this environment.
+    TxnManagerDead,
+    /// MDBX returned the data successfully, but it could not be decoded into the requested
+    /// [TableObject](crate::TableObject), e.g. a string that is not valid UTF-8 or an array of the
+    /// wrong length. Holds the reason, which is also returned by
+    /// [source()](std::error::Error::source).
     DecodeError(Box<dyn std::error::Error + Send + Sync + 'static>),
     Other(c_int),
 }
@@ -110,6 +134,30 @@ impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Error::DecodeError(reason) => write!(fmt, "{}", reason),
+            Error::OutOfOrder(index) => write!(fmt, "item {} is out of order", index),
+            Error::InvalidPageSize(size) => write!(
+                fmt,
+                "invalid page size {}: must be a power of two between {} and {}",
+                size,
+                ffi::MDBX_MIN_PAGESIZE,
+                ffi::MDBX_MAX_PAGESIZE
+            ),
+            Error::TooManyDbs(max_dbs) => write!(
+                fmt,
+                "too many databases {}: must be at most {}",
+                max_dbs,
+                ffi::MDBX_MAX_DBI
+            ),
+            Error::TxnManagerDead => write!(fmt, "transaction manager thread is no longer running"),
+            Error::VersionMismatch => write!(
+                fmt,
+                "database was written by an incompatible version of MDBX"
+            ),
+            Error::Invalid => write!(fmt, "file is not an MDBX database"),
+            Error::WannaRecovery => write!(
+                fmt,
+                "database needs recovery, which is not possible while it is opened read-only"
+            ),
             other => {
                 write!(fmt, "{}", unsafe {
                     let err = ffi::mdbx_strerror(other.to_err_code());
@@ -120,7 +168,34 @@ impl fmt::Display for Error {
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::DecodeError(reason) => Some(&**reason),
+            _ => None,
+        }
+    }
+}
+
+/// Converts to an [io::Error], so MDBX errors can be propagated with `?` from functions that
+/// return one.
+///
+/// Operating system errors, which MDBX passes through as [Error::Other], keep their error code and
+/// thus their [io::ErrorKind]. Otherwise the [Error] is wrapped with the closest matching kind,
+/// or [io::ErrorKind::Other], and can be recovered with [io::Error::into_inner()].
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        let kind = match err {
+            Error::Other(code) if code > 0 => return io::Error::from_raw_os_error(code),
+            Error::NotFound => io::ErrorKind::NotFound,
+            Error::Access => io::ErrorKind::PermissionDenied,
+            Error::InvalidValue => io::ErrorKind::InvalidInput,
+            Error::DecodeError(_) => io::ErrorKind::InvalidData,
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, err)
+    }
+}
 
 /// An MDBX result.
 pub type Result<T> = result::Result<T, Error>;
@@ -152,8 +227,46 @@ mod test {
     fn test_description() {
         assert_eq!("Permission denied", Error::from_err_code(13).to_string());
         assert_eq!(
-            "MDBX_INVALID: File is not an MDBX file",
-            Error::Invalid.to_string()
+            "MDBX_KEYEXIST: Key/data pair already exists",
+            Error::KeyExist.to_string()
         );
+        assert_eq!("file is not an MDBX database", Error::Invalid.to_string());
+    }
+
+    #[test]
+    fn test_decode_error_source() {
+        use std::error::Error as _;
+
+        let err = String::from_utf8(vec![0xff]).unwrap_err();
+        let err = Error::DecodeError(Box::new(err));
+        assert!(err
+            .source()
+            .unwrap()
+            .downcast_ref::<std::string::FromUtf8Error>()
+            .is_some());
+        assert!(Error::NotFound.source().is_none());
+    }
+
+    #[test]
+    fn test_into_io_error() {
+        let err = io::Error::from(Error::NotFound);
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(matches!(
+            err.into_inner().unwrap().downcast::<Error>().as_deref(),
+            Ok(Error::NotFound)
+        ));
+
+        assert_eq!(
+            io::Error::from(Error::Access).kind(),
+            io::ErrorKind::PermissionDenied
+        );
+
+        let err = io::Error::from(Error::Other(libc::ENOENT));
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+
+        let err = io::Error::from(Error::Corrupted);
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert_eq!(err.to_string(), Error::Corrupted.to_string());
     }
 }
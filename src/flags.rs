@@ -1,6 +1,7 @@
 use bitflags::bitflags;
 use ffi::*;
 use libc::c_uint;
+use std::fmt;
 
 /// MDBX sync mode
 #[derive(Clone, Copy, Debug)]
@@ -99,13 +100,24 @@ impl From<Mode> for EnvironmentFlags {
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct EnvironmentFlags {
+    /// Use the environment path as the data file (`MDBX_NOSUBDIR`), with the lock file next to
+    /// it named `<path>-lck`, instead of as a directory holding `mdbx.dat` and `mdbx.lck`.
     pub no_sub_dir: bool,
+    /// Open the environment for use by this process only (`MDBX_EXCLUSIVE`), which avoids the
+    /// cost of coordinating with other processes through the lock file. Opening fails with
+    /// `EAGAIN` while any other process or [Environment](crate::Environment) has it open, and
+    /// vice versa. The lock file is still created.
     pub exclusive: bool,
     pub accede: bool,
     pub mode: Mode,
     pub no_rdahead: bool,
     pub no_meminit: bool,
+    /// Merge adjacent records of the garbage collector when freed pages are returned to it
+    /// (`MDBX_COALESCE`), which keeps the freelist smaller at some CPU cost.
     pub coalesce: bool,
+    /// Reuse the most recently freed pages first (`MDBX_LIFORECLAIM`), rather than the oldest
+    /// ones. With a write-back disk cache this reduces write amplification for databases with a
+    /// lot of churn, but see the caveat on [SyncMode::UtterlyNoSync].
     pub liforeclaim: bool,
 }
 
@@ -155,6 +167,8 @@ impl EnvironmentFlags {
             flags |= ffi::MDBX_LIFORECLAIM;
         }
 
+        // Read-only transactions must not be tied to the thread that started them, as
+        // `Transaction` is `Send`. This is not configurable for that reason.
         flags |= ffi::MDBX_NOTLS;
 
         flags
@@ -165,13 +179,26 @@ bitflags! {
     #[doc="Database options."]
     #[derive(Default)]
     pub struct DatabaseFlags: c_uint {
+        /// Keys are compared in reverse order, from the last byte to the first.
         const REVERSE_KEY = MDBX_REVERSEKEY;
+        /// Keys may have multiple data items, stored in sorted order.
         const DUP_SORT = MDBX_DUPSORT;
+        /// Keys are native byte order `u32` or `u64` integers. Other key sizes are rejected with
+        /// [Error::BadValSize](crate::Error::BadValSize).
         const INTEGER_KEY = MDBX_INTEGERKEY;
+        /// With [Self::DUP_SORT], all data items have the same size.
         const DUP_FIXED = MDBX_DUPFIXED;
+        /// With [Self::DUP_SORT] and [Self::DUP_FIXED], data items are native byte order `u32` or
+        /// `u64` integers. Other data sizes are rejected with
+        /// [Error::BadValSize](crate::Error::BadValSize).
         const INTEGER_DUP = MDBX_INTEGERDUP;
+        /// With [Self::DUP_SORT], data items are compared in reverse order.
         const REVERSE_DUP = MDBX_REVERSEDUP;
+        /// Create the database if it doesn't exist. Implied by
+        /// [Transaction::create_db()](crate::Transaction::create_db).
         const CREATE = MDBX_CREATE;
+        /// Open an existing database with the flags it was created with, instead of failing with
+        /// [Error::Incompatible](crate::Error::Incompatible) if they differ from the given ones.
         const ACCEDE = MDBX_DB_ACCEDE;
     }
 }
@@ -183,6 +210,12 @@ bitflags! {
         const UPSERT = MDBX_UPSERT;
         const NO_OVERWRITE = MDBX_NOOVERWRITE;
         const NO_DUP_DATA = MDBX_NODUPDATA;
+        /// Only update an existing item. With [Transaction::put()](crate::Transaction::put),
+        /// fails with [Error::NotFound](crate::Error::NotFound) if the key doesn't exist, and with
+        /// [Error::Multival](crate::Error::Multival) if it has several
+        /// [DUP_SORT](DatabaseFlags::DUP_SORT) values, unless combined with [Self::ALLDUPS] to
+        /// replace them all. With [Cursor::put()](crate::Cursor::put), replaces the item at the
+        /// cursor position, which is how a single duplicate value is replaced.
         const CURRENT = MDBX_CURRENT;
         const ALLDUPS = MDBX_ALLDUPS;
         const RESERVE = MDBX_RESERVE;
@@ -191,3 +224,104 @@ bitflags! {
         const MULTIPLE = MDBX_MULTIPLE;
     }
 }
+
+bitflags! {
+    #[doc="Options for copying an environment."]
+    #[derive(Default)]
+    pub struct CopyFlags: c_uint {
+        /// Omit free pages from the copy and renumber the remaining ones sequentially.
+        const COMPACT = MDBX_CP_COMPACT;
+        /// Make the copy resizeable, even if the environment has a fixed size.
+        const FORCE_DYNAMIC_SIZE = MDBX_CP_FORCE_DYNAMIC_SIZE;
+    }
+}
+
+impl WriteFlags {
+    /// Insert the item, or replace its value if the key already exists. Same as
+    /// [WriteFlags::empty()].
+    pub const fn upsert() -> Self {
+        Self::UPSERT
+    }
+
+    /// Insert the item only if the key doesn't exist yet, failing with
+    /// [Error::KeyExist](crate::Error::KeyExist) otherwise.
+    pub const fn no_overwrite() -> Self {
+        Self::NO_OVERWRITE
+    }
+
+    /// Insert the item at the end of the database, which requires the key to sort after all
+    /// existing keys.
+    pub const fn append() -> Self {
+        Self::APPEND
+    }
+}
+
+/// Formats the set flags by name, e.g. `DUP_SORT | REVERSE_KEY`.
+impl fmt::Display for DatabaseFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Formats the set flags by name, e.g. `NO_OVERWRITE | APPEND`.
+impl fmt::Display for WriteFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Key ordering of a database, backed by one of MDBX's built-in comparators.
+///
+/// MDBX stores the choice alongside the database, so it must be the same every time the
+/// database is opened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyCompare {
+    /// Keys are compared as byte strings, from the first byte to the last.
+    Lexicographic,
+    /// Keys are compared as byte strings in reverse, from the last byte to the first.
+    Reverse,
+    /// Keys are native byte order `u32` or `u64` integers and are sorted numerically.
+    Integer,
+}
+
+impl Default for KeyCompare {
+    fn default() -> Self {
+        Self::Lexicographic
+    }
+}
+
+impl From<KeyCompare> for DatabaseFlags {
+    fn from(cmp: KeyCompare) -> Self {
+        match cmp {
+            KeyCompare::Lexicographic => DatabaseFlags::empty(),
+            KeyCompare::Reverse => DatabaseFlags::REVERSE_KEY,
+            KeyCompare::Integer => DatabaseFlags::INTEGER_KEY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_flags_display() {
+        let flags = DatabaseFlags::DUP_SORT | DatabaseFlags::DUP_FIXED;
+        let s = flags.to_string();
+        assert!(s.contains("DUP_SORT"));
+        assert!(s.contains("DUP_FIXED"));
+        assert_eq!(s, format!("{:?}", flags));
+
+        let s = (WriteFlags::NO_OVERWRITE | WriteFlags::APPEND).to_string();
+        assert!(s.contains("NO_OVERWRITE"));
+        assert!(s.contains("APPEND"));
+    }
+
+    #[test]
+    fn test_write_flags_constructors() {
+        assert_eq!(WriteFlags::upsert(), WriteFlags::empty());
+        assert_eq!(WriteFlags::upsert().bits(), ffi::MDBX_UPSERT);
+        assert_eq!(WriteFlags::no_overwrite().bits(), ffi::MDBX_NOOVERWRITE);
+        assert_eq!(WriteFlags::append().bits(), ffi::MDBX_APPEND);
+    }
+}
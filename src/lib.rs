@@ -5,17 +5,26 @@
 
 pub use crate::{
     codec::*,
-    cursor::{Cursor, Iter, IterDup},
+    cursor::{
+        Cursor, DupCursor, IntoIter, Iter, IterDup, IterGrouped, IterPrefix, IterRaw, IterValues,
+    },
     database::Database,
     environment::{
-        Environment, EnvironmentBuilder, EnvironmentKind, Geometry, Info, NoWriteMap, Stat,
+        AssertFn, Environment, EnvironmentBuilder, EnvironmentKind, Geometry, GeometryInfo,
+        HsrDecision, HsrFn, Info, NoWriteMap, SlowReader, Stat, ValidationIssue, ValidationReport,
         WriteMap,
     },
     error::{Error, Result},
     flags::*,
-    transaction::{Transaction, TransactionKind, RO, RW},
+    transaction::{
+        AppendOutcome, LocalTransaction, PooledTransaction, Transaction, TransactionKind,
+        TxnMetrics, RO, RW,
+    },
 };
 
+#[cfg(feature = "derive")]
+pub use mdbx_derive::TableObject;
+
 mod codec;
 mod cursor;
 mod database;
@@ -2,20 +2,25 @@ use crate::{
     database::Database,
     environment::{Environment, EnvironmentKind, NoWriteMap, TxnManagerMessage, TxnPtr},
     error::{mdbx_result, Result},
-    flags::{DatabaseFlags, WriteFlags},
-    Cursor, Error, Stat, TableObject,
+    flags::{DatabaseFlags, KeyCompare, WriteFlags},
+    Cursor, DupCursor, Error, IntoIter, ObjectLength, Stat, TableObject,
 };
 use ffi::{MDBX_txn_flags_t, MDBX_TXN_RDONLY, MDBX_TXN_READWRITE};
 use indexmap::IndexSet;
 use libc::{c_uint, c_void};
 use parking_lot::Mutex;
 use std::{
+    borrow::Cow,
     fmt,
     fmt::Debug,
     marker::PhantomData,
     mem::size_of,
+    ops::{Bound, ControlFlow, Deref},
     ptr, result, slice,
-    sync::{mpsc::sync_channel, Arc},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 mod private {
@@ -49,6 +54,60 @@ impl TransactionKind for RW {
     const OPEN_FLAGS: MDBX_txn_flags_t = MDBX_TXN_READWRITE;
 }
 
+/// The result of [Transaction::append_checked()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AppendOutcome {
+    /// The item was written.
+    Appended,
+    /// The key sorts before the last key in the database, which is held here. Keys must sort
+    /// after it to be appended.
+    OutOfOrder { expected_gt: Vec<u8> },
+    /// The key is already in the database.
+    Duplicate,
+}
+
+/// Operation counts for a transaction, as returned by [Transaction::metrics()].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TxnMetrics {
+    /// Items written by [Transaction::put()] or [Transaction::reserve()].
+    pub puts: u64,
+    /// Lookups by [Transaction::get()] or [Transaction::get_multi()], including misses.
+    pub gets: u64,
+    /// Items deleted by [Transaction::del()]. Deleting an absent item is not counted.
+    pub dels: u64,
+    /// Bytes of keys and data written by [Transaction::put()] or [Transaction::reserve()].
+    pub bytes_in: u64,
+    /// Bytes of data returned by [Transaction::get()] or [Transaction::get_multi()].
+    pub bytes_out: u64,
+}
+
+#[derive(Debug, Default)]
+struct MetricsCounters {
+    puts: AtomicU64,
+    gets: AtomicU64,
+    dels: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+impl MetricsCounters {
+    fn add(counter: &AtomicU64, n: usize) {
+        counter.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    fn record_get(&self, data_len: Option<usize>) {
+        Self::add(&self.gets, 1);
+        if let Some(len) = data_len {
+            Self::add(&self.bytes_out, len);
+        }
+    }
+
+    fn record_put(&self, key_len: usize, data_len: usize) {
+        Self::add(&self.puts, 1);
+        Self::add(&self.bytes_in, key_len + data_len);
+    }
+}
+
 /// An MDBX transaction.
 ///
 /// All database operations require a transaction.
@@ -59,7 +118,10 @@ where
 {
     txn: Arc<Mutex<*mut ffi::MDBX_txn>>,
     primed_dbis: Mutex<IndexSet<ffi::MDBX_dbi>>,
+    held_dbis: Arc<Mutex<IndexSet<ffi::MDBX_dbi>>>,
+    metrics: MetricsCounters,
     committed: bool,
+    nested: bool,
     env: &'env Environment<E>,
     _marker: PhantomData<fn(K)>,
 }
@@ -87,7 +149,10 @@ where
         Self {
             txn: Arc::new(Mutex::new(txn)),
             primed_dbis: Mutex::new(IndexSet::new()),
+            held_dbis: Arc::default(),
+            metrics: MetricsCounters::default(),
             committed: false,
+            nested: false,
             env,
             _marker: PhantomData,
         }
@@ -105,6 +170,52 @@ where
         *self.txn.lock()
     }
 
+    /// The database handles opened by this transaction, see [Environment::open_dbi()].
+    pub(crate) fn held_dbis(&self) -> &Mutex<IndexSet<ffi::MDBX_dbi>> {
+        &self.held_dbis
+    }
+
+    /// Consumes the transaction and returns the raw MDBX transaction pointer, without aborting
+    /// it.
+    ///
+    /// The caller takes ownership of the transaction and is responsible for committing or
+    /// aborting it, for example through [Transaction::from_raw()]. Read-write transactions hold
+    /// the write lock of the transaction manager thread, so they can only be ended by turning
+    /// them back into a [Transaction] with [Transaction::from_raw()].
+    ///
+    /// Database handles opened in the transaction so far are never closed, as the caller may
+    /// keep using them.
+    pub fn into_raw(mut self) -> *mut ffi::MDBX_txn {
+        self.committed = true;
+        self.held_dbis = Arc::default();
+        self.txn()
+    }
+
+    /// Takes ownership of a raw MDBX transaction pointer, such as one returned by
+    /// [Transaction::into_raw()]. The transaction is aborted on drop unless committed.
+    ///
+    /// # Safety
+    /// `txn` must be a live transaction of kind `K` that was started on `env`, and nothing else
+    /// may commit, abort or otherwise use it while the returned [Transaction] exists.
+    pub unsafe fn from_raw(env: &'env Environment<E>, txn: *mut ffi::MDBX_txn) -> Self {
+        Self::new_from_ptr(env, txn)
+    }
+
+    /// Returns the number of items and bytes read and written through this transaction so far.
+    ///
+    /// Only operations on the transaction itself are counted, not those made through a
+    /// [Cursor]. Counting is always on and costs a few relaxed atomic additions per call.
+    pub fn metrics(&self) -> TxnMetrics {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        TxnMetrics {
+            puts: load(&self.metrics.puts),
+            gets: load(&self.metrics.gets),
+            dels: load(&self.metrics.dels),
+            bytes_in: load(&self.metrics.bytes_in),
+            bytes_out: load(&self.metrics.bytes_out),
+        }
+    }
+
     /// Returns a raw pointer to the MDBX environment.
     pub fn env(&self) -> &Environment<E> {
         self.env
@@ -115,6 +226,15 @@ where
         txn_execute(&self.txn, |txn| unsafe { ffi::mdbx_txn_id(txn) })
     }
 
+    /// Returns whether the transaction has been committed, or otherwise handed off so that it
+    /// won't be aborted on drop.
+    ///
+    /// Committing consumes the transaction, so this is false for any transaction that can still
+    /// be used; it is mainly a sanity check for code passing transactions through several owners.
+    pub fn is_committed(&self) -> bool {
+        self.committed
+    }
+
     /// Gets an item from a database.
     ///
     /// This function retrieves the data associated with the given key in the
@@ -123,9 +243,26 @@ where
     /// returned. Retrieval of other items requires the use of
     /// [Cursor]. If the item is not in the database, then
     /// [None] will be returned.
+    ///
+    /// Borrowed values such as `Cow<[u8]>` stay valid across later writes in the same
+    /// transaction; see the [TableObject] implementation for `Cow` for how this is guaranteed.
     pub fn get<'txn, Key>(&'txn self, db: &Database<'txn>, key: &[u8]) -> Result<Option<Key>>
     where
         Key: TableObject<'txn>,
+    {
+        Ok(self.get_sized(db, key)?.map(|(value, _)| value))
+    }
+
+    /// Gets an item from a database along with the length of the stored value, which may differ
+    /// from the length of the decoded value, e.g. for types that only decode a prefix. Returns
+    /// [None] if the key is not in the database.
+    pub fn get_sized<'txn, Value>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: &[u8],
+    ) -> Result<Option<(Value, usize)>>
+    where
+        Value: TableObject<'txn>,
     {
         let key_val: ffi::MDBX_val = ffi::MDBX_val {
             iov_len: key.len(),
@@ -138,20 +275,256 @@ where
 
         txn_execute(&self.txn, |txn| unsafe {
             match ffi::mdbx_get(txn, db.dbi(), &key_val, &mut data_val) {
-                ffi::MDBX_SUCCESS => Key::decode_val::<K>(txn, &data_val).map(Some),
-                ffi::MDBX_NOTFOUND => Ok(None),
+                ffi::MDBX_SUCCESS => {
+                    self.metrics.record_get(Some(data_val.iov_len));
+                    Value::decode_val::<K>(txn, &data_val)
+                        .map(|value| Some((value, data_val.iov_len)))
+                }
+                ffi::MDBX_NOTFOUND => {
+                    self.metrics.record_get(None);
+                    Ok(None)
+                }
                 err_code => Err(Error::from_err_code(err_code)),
             }
         })
     }
 
+    /// Returns the length of the value stored at `key`, or [None] if the key is not in the
+    /// database, without reading or copying the value.
+    pub fn value_len<'txn>(&'txn self, db: &Database<'txn>, key: &[u8]) -> Result<Option<usize>> {
+        Ok(self.get::<ObjectLength>(db, key)?.map(|len| *len))
+    }
+
+    /// Gets an item from a database, or `default` if the key is not in the database.
+    ///
+    /// Only absence of the key is replaced by the default; any other error is returned.
+    pub fn get_or<'txn, Value>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: &[u8],
+        default: Value,
+    ) -> Result<Value>
+    where
+        Value: TableObject<'txn>,
+    {
+        Ok(self.get(db, key)?.unwrap_or(default))
+    }
+
+    /// Gets an item from a database, or the result of `f` if the key is not in the database.
+    ///
+    /// Only absence of the key is replaced by the default; any other error is returned.
+    pub fn get_or_else<'txn, Value, F>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: &[u8],
+        f: F,
+    ) -> Result<Value>
+    where
+        Value: TableObject<'txn>,
+        F: FnOnce() -> Value,
+    {
+        Ok(self.get(db, key)?.unwrap_or_else(f))
+    }
+
+    /// Gets the first item whose key sorts strictly after `key`, or [None] if there is none.
+    ///
+    /// `key` itself doesn't need to be in the database. This is the building block for keyset
+    /// pagination: pass the last key of one page to get the start of the next. For databases
+    /// with [DatabaseFlags::DUP_SORT], the first data item of the following key is returned.
+    pub fn get_gt<'txn, Key, Value>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: &[u8],
+    ) -> Result<Option<(Key, Value)>>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        let mut cursor = self.cursor(db)?;
+        if cursor.set::<()>(key)?.is_some() {
+            cursor.next_nodup()
+        } else {
+            cursor.set_range(key)
+        }
+    }
+
+    /// Gets the item with the largest key that sorts before or equal to `key`, or [None] if all
+    /// keys sort after it.
+    ///
+    /// For databases with [DatabaseFlags::DUP_SORT], the first data item of that key is
+    /// returned, as with [Transaction::get()].
+    pub fn get_le<'txn, Key, Value>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: &[u8],
+    ) -> Result<Option<(Key, Value)>>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        let mut cursor = self.cursor(db)?;
+        let floor = match cursor.set_range::<Cow<'_, [u8]>, ()>(key)? {
+            Some((found, ())) if *found == *key => return cursor.get_current(),
+            Some(_) => cursor.prev_nodup::<Cow<'_, [u8]>, ()>()?,
+            None => cursor.last()?,
+        };
+        match floor {
+            // Moves to the first data item of the key.
+            Some((floor, ())) => cursor.set_key(&floor),
+            None => Ok(None),
+        }
+    }
+
+    /// Counts the items whose key is within the given bounds, including every data item of keys
+    /// in databases with [DatabaseFlags::DUP_SORT].
+    ///
+    /// This steps through the keys in the range, so it takes time proportional to their number
+    /// rather than to the number of items. The number of items in the whole database is available
+    /// in constant time from [Transaction::db_stat()].
+    pub fn count_range<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        start: Bound<&[u8]>,
+        end: Bound<&[u8]>,
+    ) -> Result<u64> {
+        let mut cursor = self.cursor(db)?;
+        let mut item = match start {
+            Bound::Included(start) => cursor.set_range::<Cow<'_, [u8]>, ()>(start)?,
+            Bound::Excluded(start) => match cursor.set_range::<Cow<'_, [u8]>, ()>(start)? {
+                Some((found, ())) if *found == *start => cursor.next_nodup()?,
+                item => item,
+            },
+            Bound::Unbounded => cursor.first()?,
+        };
+        // Keys are compared in the database's own order, which isn't bytewise for integer,
+        // reverse or custom-ordered keys.
+        let cmp_end = |key: &[u8], end: &[u8]| {
+            let key_val = ffi::MDBX_val {
+                iov_len: key.len(),
+                iov_base: key.as_ptr() as *mut c_void,
+            };
+            let end_val = ffi::MDBX_val {
+                iov_len: end.len(),
+                iov_base: end.as_ptr() as *mut c_void,
+            };
+            txn_execute(&self.txn, |txn| unsafe {
+                ffi::mdbx_cmp(txn, db.dbi(), &key_val, &end_val)
+            })
+        };
+        let mut count = 0;
+        while let Some((key, ())) = item {
+            let in_range = match end {
+                Bound::Included(end) => cmp_end(&key, end) <= 0,
+                Bound::Excluded(end) => cmp_end(&key, end) < 0,
+                Bound::Unbounded => true,
+            };
+            if !in_range {
+                break;
+            }
+            count += cursor.dup_count()? as u64;
+            item = cursor.next_nodup()?;
+        }
+        Ok(count)
+    }
+
+    /// Gets multiple items from a database.
+    ///
+    /// Behaves like [Transaction::get()] for each key, but the transaction lock is only taken
+    /// once for the whole batch. Results are returned in the same order as `keys`, with [None]
+    /// for keys that are not in the database.
+    ///
+    /// The first error encountered aborts the lookup and is returned.
+    pub fn get_multi<'txn, Value>(
+        &'txn self,
+        db: &Database<'txn>,
+        keys: &[&[u8]],
+    ) -> Result<Vec<Option<Value>>>
+    where
+        Value: TableObject<'txn>,
+    {
+        txn_execute(&self.txn, |txn| {
+            keys.iter()
+                .map(|key| {
+                    let key_val: ffi::MDBX_val = ffi::MDBX_val {
+                        iov_len: key.len(),
+                        iov_base: key.as_ptr() as *mut c_void,
+                    };
+                    let mut data_val: ffi::MDBX_val = ffi::MDBX_val {
+                        iov_len: 0,
+                        iov_base: ptr::null_mut(),
+                    };
+
+                    unsafe {
+                        match ffi::mdbx_get(txn, db.dbi(), &key_val, &mut data_val) {
+                            ffi::MDBX_SUCCESS => {
+                                self.metrics.record_get(Some(data_val.iov_len));
+                                Value::decode_val::<K>(txn, &data_val).map(Some)
+                            }
+                            ffi::MDBX_NOTFOUND => {
+                                self.metrics.record_get(None);
+                                Ok(None)
+                            }
+                            err_code => Err(Error::from_err_code(err_code)),
+                        }
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Iterates over every key/data pair in the database, in key order.
+    ///
+    /// The returned iterator owns the cursor it reads with, which is closed when the iterator is
+    /// dropped.
+    pub fn iter<'txn, Key, Value>(
+        &'txn self,
+        db: &Database<'txn>,
+    ) -> Result<IntoIter<'txn, K, Key, Value>>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+    {
+        Ok(IntoIter::new(
+            self.cursor(db)?,
+            ffi::MDBX_FIRST,
+            ffi::MDBX_NEXT,
+        ))
+    }
+
+    /// Calls `f` on every key/data pair in the database, in key order.
+    ///
+    /// Iteration stops early if `f` returns [ControlFlow::Break]. Errors from decoding or from
+    /// `f` itself are returned immediately.
+    pub fn for_each<'txn, Key, Value, F>(&'txn self, db: &Database<'txn>, mut f: F) -> Result<()>
+    where
+        Key: TableObject<'txn>,
+        Value: TableObject<'txn>,
+        F: FnMut(Key, Value) -> Result<ControlFlow<()>>,
+    {
+        let mut cursor = self.cursor(db)?;
+        let mut item = cursor.first()?;
+        while let Some((key, value)) = item {
+            if let ControlFlow::Break(()) = f(key, value)? {
+                break;
+            }
+            item = cursor.next()?;
+        }
+        Ok(())
+    }
+
     /// Commits the transaction.
     ///
     /// Any pending operations will be saved.
+    ///
+    /// For read-write transactions this returns [Error::TxnManagerDead] rather than panicking if
+    /// the transaction manager thread has stopped, in which case whether the commit took effect is
+    /// unknown.
     pub fn commit(self) -> Result<bool> {
         self.commit_and_rebind_open_dbs().map(|v| v.0)
     }
 
+    /// Marks the database handle to be returned by [Transaction::commit_and_rebind_open_dbs()],
+    /// see also [Environment::open_db_permanent()](crate::Environment::open_db_permanent).
     pub fn prime_for_permaopen(&self, db: Database<'_>) {
         self.primed_dbis.lock().insert(db.dbi());
     }
@@ -163,17 +536,11 @@ where
         let result = if K::ONLY_CLEAN {
             mdbx_result(unsafe { ffi::mdbx_txn_commit_ex(txn, ptr::null_mut()) })
         } else {
-            let (sender, rx) = sync_channel(0);
             self.env
-                .txn_manager
-                .as_ref()
-                .unwrap()
-                .send(TxnManagerMessage::Commit {
+                .txn_manager_call(|sender| TxnManagerMessage::Commit {
                     tx: TxnPtr(txn),
                     sender,
                 })
-                .unwrap();
-            rx.recv().unwrap()
         };
         self.committed = true;
         result.map(|v| {
@@ -182,7 +549,10 @@ where
                 self.primed_dbis
                     .lock()
                     .iter()
-                    .map(|&dbi| Database::new_from_ptr(dbi))
+                    .map(|&dbi| {
+                        self.env.pin_dbi(dbi);
+                        Database::new_from_ptr(dbi)
+                    })
                     .collect(),
             )
         })
@@ -206,9 +576,10 @@ where
     /// Gets the option flags for the given database in the transaction.
     pub fn db_flags<'txn>(&'txn self, db: &Database<'txn>) -> Result<DatabaseFlags> {
         let mut flags: c_uint = 0;
+        let mut state: c_uint = 0;
         unsafe {
             mdbx_result(txn_execute(&self.txn, |txn| {
-                ffi::mdbx_dbi_flags_ex(txn, db.dbi(), &mut flags, ptr::null_mut())
+                ffi::mdbx_dbi_flags_ex(txn, db.dbi(), &mut flags, &mut state)
             }))?;
         }
         Ok(DatabaseFlags::from_bits_truncate(flags))
@@ -225,10 +596,233 @@ where
         }
     }
 
+    /// Returns the number of bytes occupied by the database, see [Stat::total_size_bytes()].
+    pub fn db_size<'txn>(&'txn self, db: &Database<'txn>) -> Result<u64> {
+        Ok(self.db_stat(db)?.total_size_bytes())
+    }
+
+    /// Returns the id of the last committed transaction that modified the database.
+    ///
+    /// This is cheap, so it can be used to tell whether anything derived from the database needs
+    /// to be recomputed: the value only changes when a write to this database is committed.
+    /// Changes made by a read-write transaction are not reflected until it commits.
+    pub fn db_last_change_txn<'txn>(&'txn self, db: &Database<'txn>) -> Result<u64> {
+        self.db_stat(db).map(|stat| stat.mod_txnid())
+    }
+
+    /// Opens the database with the given name and retrieves its statistics.
+    ///
+    /// Returns [Error::NotFound] if `name` does not refer to an existing named database.
+    pub fn db_stat_by_name(&self, name: Option<&str>) -> Result<Stat> {
+        let db = self.open_db(name)?;
+        self.db_stat(&db)
+    }
+
+    /// Lists the named databases in the environment along with their statistics, in order of
+    /// name.
+    ///
+    /// Names that are not valid UTF-8 are skipped, as they can't be opened with
+    /// [Transaction::open_db()]. With
+    /// [EnvironmentBuilder::set_close_dbs()](crate::EnvironmentBuilder::set_close_dbs), handles
+    /// opened just for the listing are released again straight away, so it needs only one slot
+    /// beyond the handles already open, however many databases there are. Otherwise every
+    /// database stays open and counts against
+    /// [EnvironmentBuilder::set_max_dbs()](crate::EnvironmentBuilder::set_max_dbs).
+    pub fn iter_dbs(&self) -> Result<Vec<(String, Stat)>> {
+        let main = self.open_db(None)?;
+        let mut cursor = self.cursor(&main)?;
+        let mut dbs = Vec::new();
+        for item in cursor.iter_start::<Cow<'_, [u8]>, ()>() {
+            let name = match String::from_utf8(item?.0.into_owned()) {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            let mut fresh = false;
+            let res = txn_execute(&self.txn, |txn| {
+                self.env.open_dbi(&self.held_dbis, || {
+                    let dbi = unsafe { Database::open_raw(txn, Some(&name), 0)?.dbi() };
+                    fresh = !self.held_dbis.lock().contains(&dbi);
+                    Ok(dbi)
+                })
+            });
+            let dbi = match res {
+                Ok(dbi) => dbi,
+                // A plain item rather than a named database.
+                Err(Error::Incompatible) => continue,
+                Err(e) => return Err(e),
+            };
+            let stat = self.db_stat(&Database::new_from_ptr(dbi));
+            if fresh {
+                self.held_dbis.lock().swap_remove(&dbi);
+                self.env.release_dbis([dbi]);
+            }
+            dbs.push((name, stat?));
+        }
+        Ok(dbs)
+    }
+
     /// Open a new cursor on the given database.
     pub fn cursor<'txn>(&'txn self, db: &Database<'txn>) -> Result<Cursor<'txn, K>> {
         Cursor::new(self, db)
     }
+
+    /// Open a new cursor on the given [DatabaseFlags::DUP_SORT] database, failing with
+    /// [Error::Incompatible] if the database doesn't have that flag.
+    pub fn cursor_dup<'txn>(&'txn self, db: &Database<'txn>) -> Result<DupCursor<'txn, K>> {
+        DupCursor::new(self, db)
+    }
+}
+
+/// A read-only transaction for use from a single thread.
+///
+/// [Transaction] is [Send], so every operation on it takes a lock around the transaction handle.
+/// A `LocalTransaction` can't leave the thread that created it and skips that lock, which makes a
+/// difference for tight lookup loops. Only the lookups themselves are provided; use
+/// [Environment::begin_ro_txn()] for anything else.
+pub struct LocalTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    txn: *mut ffi::MDBX_txn,
+    held_dbis: Mutex<IndexSet<ffi::MDBX_dbi>>,
+    env: &'env Environment<E>,
+}
+
+impl<'env, E> LocalTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    pub(crate) fn new(env: &'env Environment<E>) -> Result<Self> {
+        let mut txn: *mut ffi::MDBX_txn = ptr::null_mut();
+        unsafe {
+            mdbx_result(ffi::mdbx_txn_begin_ex(
+                env.env(),
+                ptr::null_mut(),
+                RO::OPEN_FLAGS,
+                &mut txn,
+                ptr::null_mut(),
+            ))?;
+        }
+        Ok(Self {
+            txn,
+            held_dbis: Mutex::new(IndexSet::new()),
+            env,
+        })
+    }
+
+    /// Returns the transaction id.
+    pub fn id(&self) -> u64 {
+        unsafe { ffi::mdbx_txn_id(self.txn) }
+    }
+
+    /// Opens a handle to an MDBX database, see [Transaction::open_db()].
+    pub fn open_db<'txn>(&'txn self, name: Option<&str>) -> Result<Database<'txn>> {
+        let dbi = self.env.open_dbi(&self.held_dbis, || unsafe {
+            Database::open_raw(self.txn, name, 0).map(|db| db.dbi())
+        })?;
+        Ok(Database::new_from_ptr(dbi))
+    }
+
+    /// Gets an item from a database, see [Transaction::get()].
+    pub fn get<'txn, Key>(&'txn self, db: &Database<'txn>, key: &[u8]) -> Result<Option<Key>>
+    where
+        Key: TableObject<'txn>,
+    {
+        let key_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: key.len(),
+            iov_base: key.as_ptr() as *mut c_void,
+        };
+        let mut data_val: ffi::MDBX_val = ffi::MDBX_val {
+            iov_len: 0,
+            iov_base: ptr::null_mut(),
+        };
+
+        unsafe {
+            match ffi::mdbx_get(self.txn, db.dbi(), &key_val, &mut data_val) {
+                ffi::MDBX_SUCCESS => Key::decode_val::<RO>(self.txn, &data_val).map(Some),
+                ffi::MDBX_NOTFOUND => Ok(None),
+                err_code => Err(Error::from_err_code(err_code)),
+            }
+        }
+    }
+}
+
+impl<'env, E> fmt::Debug for LocalTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        f.debug_struct("LocalTransaction").finish()
+    }
+}
+
+impl<'env, E> Drop for LocalTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    fn drop(&mut self) {
+        unsafe {
+            ffi::mdbx_txn_abort(self.txn);
+        }
+        self.env.release_dbis(self.held_dbis.get_mut().drain(..));
+    }
+}
+
+/// Returns the flags of a database in the given raw transaction, which must be live.
+pub(crate) unsafe fn dbi_flags(
+    txn: *mut ffi::MDBX_txn,
+    dbi: ffi::MDBX_dbi,
+) -> Result<DatabaseFlags> {
+    let mut flags: c_uint = 0;
+    let mut state: c_uint = 0;
+    mdbx_result(ffi::mdbx_dbi_flags_ex(txn, dbi, &mut flags, &mut state))?;
+    Ok(DatabaseFlags::from_bits_truncate(flags))
+}
+
+fn is_integer_size(len: usize) -> bool {
+    len == 4 || len == 8
+}
+
+/// Returns [Error::BadValSize] if the key or data size is not valid for a
+/// [DatabaseFlags::INTEGER_KEY] or [DatabaseFlags::INTEGER_DUP] database with the given flags.
+///
+/// MDBX returns the same error itself, but debug builds of libmdbx trip an internal assertion
+/// first, which aborts the process. mdbx-sys builds libmdbx without `NDEBUG`, so assertions are
+/// enabled by default.
+pub(crate) fn check_integer_sizes_for(
+    flags: DatabaseFlags,
+    key_len: usize,
+    data_len: usize,
+) -> Result<()> {
+    if (flags.contains(DatabaseFlags::INTEGER_KEY) && !is_integer_size(key_len))
+        || (flags.contains(DatabaseFlags::INTEGER_DUP) && !is_integer_size(data_len))
+    {
+        return Err(Error::BadValSize);
+    }
+    Ok(())
+}
+
+/// Like [check_integer_sizes_for()], but only looks up the flags of the database if the sizes
+/// could be invalid, for writing a single item.
+pub(crate) unsafe fn check_integer_sizes(
+    txn: *mut ffi::MDBX_txn,
+    dbi: ffi::MDBX_dbi,
+    key_len: usize,
+    data_len: usize,
+) -> Result<()> {
+    if is_integer_size(key_len) && is_integer_size(data_len) {
+        return Ok(());
+    }
+    check_integer_sizes_for(dbi_flags(txn, dbi)?, key_len, data_len)
+}
+
+/// MDBX doesn't support `MDBX_RESERVE` in [DatabaseFlags::DUP_SORT] databases. Checking up front
+/// reports this as [Error::Incompatible] before anything is written.
+pub(crate) fn check_reservable(flags: DatabaseFlags) -> Result<()> {
+    if flags.contains(DatabaseFlags::DUP_SORT) {
+        return Err(Error::Incompatible);
+    }
+    Ok(())
 }
 
 pub(crate) fn txn_execute<F: FnOnce(*mut ffi::MDBX_txn) -> T, T>(
@@ -251,6 +845,38 @@ where
         Database::new(self, name, flags.bits())
     }
 
+    /// Commits the transaction and begins a new read-write transaction in the same round-trip to
+    /// the transaction manager, so no other writer can get in between.
+    ///
+    /// The returned transaction sees everything committed by this one. The outer [Result] is the
+    /// outcome of the commit and the inner one that of beginning the new transaction, so a
+    /// failure to begin doesn't hide a commit that has already taken effect.
+    ///
+    /// Fails with [Error::BadTxn] without committing anything if called on a nested transaction.
+    pub fn commit_and_begin(self) -> Result<(bool, Result<Transaction<'env, RW, E>>)> {
+        self.commit_and_begin_with_flags(RW::OPEN_FLAGS)
+    }
+
+    fn commit_and_begin_with_flags(
+        mut self,
+        flags: ffi::MDBX_txn_flags_t,
+    ) -> Result<(bool, Result<Transaction<'env, RW, E>>)> {
+        if self.nested {
+            return Err(Error::BadTxn);
+        }
+        let result = txn_execute(&self.txn, |txn| {
+            self.env
+                .txn_manager_call(|sender| TxnManagerMessage::CommitAndBegin {
+                    tx: TxnPtr(txn),
+                    flags,
+                    sender,
+                })
+        });
+        self.committed = true;
+        let env = self.env;
+        result.map(|(v, txn)| (v, txn.map(|txn| Transaction::new_from_ptr(env, txn.0))))
+    }
+
     /// Opens a handle to an MDBX database, creating the database if necessary.
     ///
     /// If the database is already created, the given option flags will be added to it.
@@ -261,6 +887,10 @@ where
     /// case the environment must be configured to allow named databases through
     /// [EnvironmentBuilder::set_max_dbs()](crate::EnvironmentBuilder::set_max_dbs).
     ///
+    /// If `flags` contains [DatabaseFlags::ACCEDE], an existing database is opened with the
+    /// flags it was created with, whatever they are, and the other flags are only used to
+    /// create the database if it doesn't exist yet.
+    ///
     /// This function will fail with [Error::BadRslot](crate::error::Error::BadRslot) if called by a thread with an open
     /// transaction.
     pub fn create_db<'txn>(
@@ -268,7 +898,33 @@ where
         name: Option<&str>,
         flags: DatabaseFlags,
     ) -> Result<Database<'txn>> {
-        self.open_db_with_flags(name, flags | DatabaseFlags::CREATE)
+        if flags.contains(DatabaseFlags::ACCEDE) {
+            // MDBX rejects ACCEDE in combination with any other flag, so open and create are
+            // separate steps.
+            match self.open_db_with_flags(name, DatabaseFlags::ACCEDE) {
+                Err(Error::NotFound) => {}
+                res => return res,
+            }
+        }
+        self.open_db_with_flags(
+            name,
+            (flags - DatabaseFlags::ACCEDE) | DatabaseFlags::CREATE,
+        )
+    }
+
+    /// Opens a handle to an MDBX database with the given key ordering, creating the database
+    /// if necessary.
+    ///
+    /// This behaves like [Transaction::create_db()], with `cmp` selecting one of MDBX's built-in
+    /// comparators. Opening an existing database with a different ordering than it was created
+    /// with fails with [Error::Incompatible].
+    pub fn create_db_with_compare<'txn>(
+        &'txn self,
+        name: Option<&str>,
+        flags: DatabaseFlags,
+        cmp: KeyCompare,
+    ) -> Result<Database<'txn>> {
+        self.create_db(name, flags | cmp.into())
     }
 
     /// Stores an item into a database.
@@ -277,6 +933,8 @@ where
     /// behavior is to enter the new key/data pair, replacing any previously
     /// existing key if duplicates are disallowed, or adding a duplicate data
     /// item if duplicates are allowed ([DatabaseFlags::DUP_SORT]).
+    ///
+    /// Pass [WriteFlags::CURRENT] to only update a key that already exists.
     pub fn put<'txn>(
         &'txn self,
         db: &Database<'txn>,
@@ -294,21 +952,178 @@ where
             iov_len: data.len(),
             iov_base: data.as_ptr() as *mut c_void,
         };
-        mdbx_result(txn_execute(&self.txn, |txn| unsafe {
-            ffi::mdbx_put(txn, db.dbi(), &key_val, &mut data_val, flags.bits())
-        }))?;
+        txn_execute(&self.txn, |txn| unsafe {
+            check_integer_sizes(txn, db.dbi(), key.len(), data.len())?;
+            mdbx_result(ffi::mdbx_put(
+                txn,
+                db.dbi(),
+                &key_val,
+                &mut data_val,
+                flags.bits(),
+            ))
+        })?;
+        self.metrics.record_put(key.len(), data.len());
 
         Ok(())
     }
 
-    /// Returns a buffer which can be used to write a value into the item at the
-    /// given key and with the given length. The buffer must be completely
-    /// filled by the caller.
-    pub fn reserve<'txn>(
+    /// Stores an item into a database like [Transaction::put()], returning the value it
+    /// replaces, or [None] if the key was not in the database.
+    ///
+    /// Fails with [Error::Incompatible] for [DatabaseFlags::DUP_SORT] databases, where a key can
+    /// have several values and there is no single one to replace.
+    pub fn swap<'txn, Value>(
         &'txn self,
         db: &Database<'txn>,
         key: impl AsRef<[u8]>,
-        len: usize,
+        data: impl AsRef<[u8]>,
+        flags: WriteFlags,
+    ) -> Result<Option<Value>>
+    where
+        Value: TableObject<'txn>,
+    {
+        if self.db_flags(db)?.contains(DatabaseFlags::DUP_SORT) {
+            return Err(Error::Incompatible);
+        }
+        let key = key.as_ref();
+        let old = self.get(db, key)?;
+        self.put(db, key, data, flags)?;
+        Ok(old)
+    }
+
+    /// Overwrites the value of a key that is already in the database, returning `false` without
+    /// writing anything if it isn't.
+    ///
+    /// This is [Transaction::put()] with [WriteFlags::CURRENT] added to `flags`. For
+    /// [DatabaseFlags::DUP_SORT] databases it fails with [Error::Multival] if the key has several
+    /// values, unless `flags` contains [WriteFlags::ALLDUPS] to replace them all.
+    pub fn replace<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl AsRef<[u8]>,
+        data: impl AsRef<[u8]>,
+        flags: WriteFlags,
+    ) -> Result<bool> {
+        match self.put(db, key, data, flags | WriteFlags::CURRENT) {
+            Ok(()) => Ok(true),
+            Err(Error::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stores several items into a database, as [Transaction::put()] would, with the same flags.
+    ///
+    /// The transaction is locked once for the whole batch rather than once per item, which
+    /// matters for many small writes. If an item fails, the error is returned and the items
+    /// before it remain written; it is up to the caller whether to abort the transaction.
+    pub fn put_batch<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        pairs: &[(&[u8], &[u8])],
+        flags: WriteFlags,
+    ) -> Result<()> {
+        txn_execute(&self.txn, |txn| {
+            let db_flags = unsafe { dbi_flags(txn, db.dbi())? };
+            for (key, data) in pairs {
+                let key_val: ffi::MDBX_val = ffi::MDBX_val {
+                    iov_len: key.len(),
+                    iov_base: key.as_ptr() as *mut c_void,
+                };
+                let mut data_val: ffi::MDBX_val = ffi::MDBX_val {
+                    iov_len: data.len(),
+                    iov_base: data.as_ptr() as *mut c_void,
+                };
+                check_integer_sizes_for(db_flags, key.len(), data.len())?;
+                unsafe {
+                    mdbx_result(ffi::mdbx_put(
+                        txn,
+                        db.dbi(),
+                        &key_val,
+                        &mut data_val,
+                        flags.bits(),
+                    ))?;
+                }
+                self.metrics.record_put(key.len(), data.len());
+            }
+            Ok(())
+        })
+    }
+
+    /// Appends pre-sorted key/data pairs to the end of a database.
+    ///
+    /// Each item is written with [WriteFlags::APPEND] through a single cursor, which is much
+    /// faster than individual [Transaction::put()] calls for large imports. Keys must be strictly
+    /// increasing and sort after any key already in the database; otherwise
+    /// [Error::OutOfOrder] is returned with the index of the offending item. Items before it
+    /// remain written.
+    ///
+    /// Returns the number of items inserted.
+    pub fn bulk_load<'txn, Key, Data>(
+        &'txn self,
+        db: &Database<'txn>,
+        items: impl IntoIterator<Item = (Key, Data)>,
+    ) -> Result<usize>
+    where
+        Key: AsRef<[u8]>,
+        Data: AsRef<[u8]>,
+    {
+        let db_flags = self.db_flags(db)?;
+        let mut cursor = self.cursor(db)?;
+        let mut count = 0;
+        for (key, data) in items {
+            cursor
+                .put_with_db_flags(key.as_ref(), data.as_ref(), WriteFlags::APPEND, db_flags)
+                .map_err(|e| match e {
+                    Error::KeyMismatch => Error::OutOfOrder(count),
+                    other => other,
+                })?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Appends a single key/data pair to the end of a database, reporting why it was rejected
+    /// rather than failing if it doesn't sort strictly after the last key.
+    ///
+    /// Rejected items are not written and leave the transaction usable, so a migration can log
+    /// and skip them. Meant for databases without [DatabaseFlags::DUP_SORT].
+    pub fn append_checked<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl AsRef<[u8]>,
+        data: impl AsRef<[u8]>,
+    ) -> Result<AppendOutcome> {
+        let key = key.as_ref();
+        match self.put(db, key, data, WriteFlags::APPEND | WriteFlags::NO_OVERWRITE) {
+            Ok(()) => Ok(AppendOutcome::Appended),
+            Err(Error::KeyExist) => Ok(AppendOutcome::Duplicate),
+            Err(Error::KeyMismatch) => {
+                let last = self
+                    .cursor(db)?
+                    .last::<Vec<u8>, ()>()?
+                    .map(|(last, ())| last)
+                    .unwrap_or_default();
+                if last == key {
+                    Ok(AppendOutcome::Duplicate)
+                } else {
+                    Ok(AppendOutcome::OutOfOrder { expected_gt: last })
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns a buffer which can be used to write a value into the item at the
+    /// given key and with the given length. The buffer must be completely
+    /// filled by the caller.
+    ///
+    /// Reserving is not supported in [DatabaseFlags::DUP_SORT] databases, where this returns
+    /// [Error::Incompatible] without modifying the transaction.
+    pub fn reserve<'txn>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl AsRef<[u8]>,
+        len: usize,
         flags: WriteFlags,
     ) -> Result<&'txn mut [u8]> {
         let key = key.as_ref();
@@ -321,15 +1136,19 @@ where
             iov_base: ptr::null_mut::<c_void>(),
         };
         unsafe {
-            mdbx_result(txn_execute(&self.txn, |txn| {
-                ffi::mdbx_put(
+            txn_execute(&self.txn, |txn| {
+                let db_flags = dbi_flags(txn, db.dbi())?;
+                check_reservable(db_flags)?;
+                check_integer_sizes_for(db_flags, key.len(), len)?;
+                mdbx_result(ffi::mdbx_put(
                     txn,
                     db.dbi(),
                     &key_val,
                     &mut data_val,
                     flags.bits() | ffi::MDBX_RESERVE,
-                )
-            }))?;
+                ))
+            })?;
+            self.metrics.record_put(key.len(), len);
             Ok(slice::from_raw_parts_mut(
                 data_val.iov_base as *mut u8,
                 data_val.iov_len,
@@ -337,6 +1156,26 @@ where
         }
     }
 
+    /// Writes a value of length `len` at the given key, filled in place by `f`.
+    ///
+    /// This is [Transaction::reserve()] without the buffer escaping: `f` receives the reserved
+    /// buffer and must overwrite all of it, as it is not initialized. Unlike [Transaction::put()],
+    /// the value doesn't need to be assembled in memory first.
+    pub fn put_with<'txn, F>(
+        &'txn self,
+        db: &Database<'txn>,
+        key: impl AsRef<[u8]>,
+        len: usize,
+        flags: WriteFlags,
+        f: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        f(self.reserve(db, key, len, flags)?);
+        Ok(())
+    }
+
     /// Delete items from a database.
     /// This function removes key/data pairs from the database.
     ///
@@ -370,20 +1209,57 @@ where
                 }
             })
         })
-        .map(|_| true)
+        .map(|_| {
+            MetricsCounters::add(&self.metrics.dels, 1);
+            true
+        })
         .or_else(|e| match e {
             Error::NotFound => Ok(false),
             other => Err(other),
         })
     }
 
+    /// Deletes every key/data pair for which `pred` returns `false`, in a single scan of the
+    /// database.
+    ///
+    /// For databases with [DatabaseFlags::DUP_SORT], `pred` is called once per data item.
+    ///
+    /// Returns the number of items deleted.
+    pub fn retain<'txn, F>(&'txn self, db: &Database<'txn>, mut pred: F) -> Result<usize>
+    where
+        F: FnMut(&[u8], &[u8]) -> bool,
+    {
+        let mut cursor = self.cursor(db)?;
+        let mut deleted = 0;
+        let mut item = cursor.first::<Cow<'_, [u8]>, Cow<'_, [u8]>>()?;
+        while let Some((key, value)) = item {
+            if !pred(&key, &value) {
+                cursor.del(WriteFlags::empty())?;
+                deleted += 1;
+            }
+            // After a delete the cursor already sits on the following item, which MDBX_NEXT
+            // returns rather than skipping.
+            item = cursor.next()?;
+        }
+        Ok(deleted)
+    }
+
     /// Empties the given database. All items will be removed.
-    pub fn clear_db<'txn>(&'txn self, db: &Database<'txn>) -> Result<()> {
-        mdbx_result(txn_execute(&self.txn, |txn| unsafe {
-            ffi::mdbx_drop(txn, db.dbi(), false)
-        }))?;
+    ///
+    /// Returns the number of items removed.
+    pub fn clear_db<'txn>(&'txn self, db: &Database<'txn>) -> Result<usize> {
+        let mut stat = Stat::new();
+        txn_execute(&self.txn, |txn| unsafe {
+            mdbx_result(ffi::mdbx_dbi_stat(
+                txn,
+                db.dbi(),
+                stat.mdb_stat(),
+                size_of::<Stat>(),
+            ))?;
+            mdbx_result(ffi::mdbx_drop(txn, db.dbi(), false))
+        })?;
 
-        Ok(())
+        Ok(stat.entries())
     }
 
     /// Drops the database from the environment.
@@ -412,27 +1288,116 @@ where
 
         Ok(())
     }
+
+    /// Moves the transaction to the latest committed snapshot, returning its transaction id.
+    ///
+    /// Long-lived read transactions keep the pages of their snapshot from being reused, so the
+    /// database grows while they are open. Refreshing releases the old snapshot, and makes data
+    /// committed since the transaction began visible. Taking `&mut self` ensures no cursors or
+    /// values borrowed from the old snapshot outlive it; databases need to be reopened.
+    ///
+    /// If the transaction can't be renewed, the error is returned and the transaction is left
+    /// unusable.
+    ///
+    /// Newer MDBX versions can instead park a read transaction (`mdbx_txn_park`), releasing its
+    /// snapshot while keeping the reader slot. The bundled libmdbx 0.11 doesn't support parking,
+    /// so refreshing when the reader is next needed is the way to release the snapshot.
+    pub fn refresh(&mut self) -> Result<u64> {
+        txn_execute(&self.txn, |txn| unsafe {
+            mdbx_result(ffi::mdbx_txn_reset(txn))?;
+            mdbx_result(ffi::mdbx_txn_renew(txn))?;
+            Ok(ffi::mdbx_txn_id(txn))
+        })
+    }
+
+    /// Resets the transaction, releasing its snapshot, and hands back the raw pointer so it can
+    /// later be renewed.
+    pub(crate) fn into_reset(mut self) -> Result<TxnPtr> {
+        let txn = self.txn();
+        mdbx_result(unsafe { ffi::mdbx_txn_reset(txn) })?;
+        self.committed = true;
+        Ok(TxnPtr(txn))
+    }
+}
+
+/// A read-only transaction taken from the environment's pool by [Environment::ro_txn()].
+///
+/// On drop the transaction is reset and returned to the pool.
+pub struct PooledTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    txn: Option<Transaction<'env, RO, E>>,
+}
+
+impl<'env, E> PooledTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    pub(crate) fn new(txn: Transaction<'env, RO, E>) -> Self {
+        Self { txn: Some(txn) }
+    }
+}
+
+impl<'env, E> Deref for PooledTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    type Target = Transaction<'env, RO, E>;
+
+    fn deref(&self) -> &Self::Target {
+        self.txn.as_ref().unwrap()
+    }
+}
+
+impl<'env, E> fmt::Debug for PooledTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        f.debug_struct("PooledTransaction").finish()
+    }
+}
+
+impl<'env, E> Drop for PooledTransaction<'env, E>
+where
+    E: EnvironmentKind,
+{
+    fn drop(&mut self) {
+        if let Some(txn) = self.txn.take() {
+            let env = txn.env;
+            if let Ok(txn) = txn.into_reset() {
+                env.release_ro_txn(txn);
+            }
+        }
+    }
 }
 
 impl<'env> Transaction<'env, RW, NoWriteMap> {
     /// Begins a new nested transaction inside of this transaction.
+    ///
+    /// Committing the nested transaction merges its changes into this transaction; they are
+    /// only written to the database once this transaction is committed. Dropping the nested
+    /// transaction without committing discards its changes and leaves this transaction intact.
+    ///
+    /// Only available on [NoWriteMap] environments, as MDBX does not support nested
+    /// transactions in `MDBX_WRITEMAP` mode.
     pub fn begin_nested_txn(&mut self) -> Result<Transaction<'_, RW, NoWriteMap>> {
         txn_execute(&self.txn, |txn| {
-            let (tx, rx) = sync_channel(0);
             self.env
-                .txn_manager
-                .as_ref()
-                .unwrap()
-                .send(TxnManagerMessage::Begin {
+                .txn_manager_call(|sender| TxnManagerMessage::Begin {
                     parent: TxnPtr(txn),
                     flags: RW::OPEN_FLAGS,
-                    sender: tx,
+                    sender,
+                })
+                .map(|ptr| {
+                    // Changes to the databases the nested transaction opens become part of this
+                    // transaction, so its handles stay open until this one ends.
+                    let mut nested = Transaction::new_from_ptr(self.env, ptr.0);
+                    nested.held_dbis = self.held_dbis.clone();
+                    nested.nested = true;
+                    nested
                 })
-                .unwrap();
-
-            rx.recv()
-                .unwrap()
-                .map(|ptr| Transaction::new_from_ptr(self.env, ptr.0))
         })
     }
 }
@@ -460,23 +1425,39 @@ where
                         ffi::mdbx_txn_abort(txn);
                     }
                 } else {
-                    let (sender, rx) = sync_channel(0);
-                    self.env
-                        .txn_manager
-                        .as_ref()
-                        .unwrap()
-                        .send(TxnManagerMessage::Abort {
+                    // If the transaction manager is gone there is nothing left to abort with, and
+                    // panicking here would only mask the original failure.
+                    match self
+                        .env
+                        .txn_manager_call(|sender| TxnManagerMessage::Abort {
                             tx: TxnPtr(txn),
                             sender,
-                        })
-                        .unwrap();
-                    rx.recv().unwrap().unwrap();
+                        }) {
+                        Ok(_) | Err(Error::TxnManagerDead) => {}
+                        Err(e) => panic!("failed to abort transaction: {}", e),
+                    }
                 }
             }
-        })
+        });
+
+        // Nested transactions share the handles of their parent, which releases them.
+        if let Some(held) = Arc::get_mut(&mut self.held_dbis) {
+            self.env.release_dbis(held.get_mut().drain(..));
+        }
+
+        // Every open cursor holds a reference to the transaction handle, so any count above one
+        // means a cursor has outlived the transaction it points into.
+        debug_assert_eq!(
+            Arc::strong_count(&self.txn),
+            1,
+            "transaction dropped while cursors are still open"
+        );
     }
 }
 
+// Sound because every environment is opened with `MDBX_NOTLS`, so read-only transactions do not
+// own a thread-local reader slot, and read-write transactions are started, committed and aborted
+// on the transaction manager thread.
 unsafe impl<'env, K, E> Send for Transaction<'env, K, E>
 where
     K: TransactionKind,
@@ -493,10 +1474,14 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::{error::*, flags::*, NoWriteMap};
+    use crate::{
+        error::*, flags::*, AppendOutcome, KeyBuf, NoWriteMap, Transaction, TxnMetrics, RW,
+    };
     use std::{
         borrow::Cow,
         io::Write,
+        ops::{Bound, ControlFlow},
+        ptr,
         sync::{Arc, Barrier},
         thread::{self, JoinHandle},
     };
@@ -511,169 +1496,1605 @@ mod test {
 
         let txn = env.begin_rw_txn().unwrap();
         let db = txn.open_db(None).unwrap();
-        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
-        txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
-        txn.put(&db, b"key3", b"val3", WriteFlags::empty()).unwrap();
-        txn.commit().unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key3", b"val3", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
+        assert_eq!(txn.get(&db, b"key2").unwrap(), Some(*b"val2"));
+        assert_eq!(txn.get(&db, b"key3").unwrap(), Some(*b"val3"));
+        assert_eq!(txn.get::<()>(&db, b"key").unwrap(), None);
+
+        txn.del(&db, b"key1", None).unwrap();
+        assert_eq!(txn.get::<()>(&db, b"key1").unwrap(), None);
+    }
+
+    #[test]
+    #[cfg_attr(not(debug_assertions), ignore)]
+    #[should_panic(expected = "transaction dropped while cursors are still open")]
+    fn test_drop_with_open_cursor() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        std::mem::forget(txn.cursor(&db).unwrap());
+        drop(txn);
+    }
+
+    #[test]
+    fn test_bulk_load() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let count = txn
+            .bulk_load(
+                &db,
+                (0..10_000u32).map(|i| (i.to_be_bytes(), format!("data{}", i))),
+            )
+            .unwrap();
+        assert_eq!(count, 10_000);
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.db_stat(&db).unwrap().entries(), 10_000);
+        let mut cursor = txn.cursor(&db).unwrap();
+        for (i, item) in cursor.iter_start::<[u8; 4], Vec<u8>>().enumerate() {
+            let (key, data) = item.unwrap();
+            assert_eq!(u32::from_be_bytes(key), i as u32);
+            assert_eq!(data, format!("data{}", i).into_bytes());
+        }
+        drop(cursor);
+        drop(txn);
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let items = [10_000u32, 10_001, 5, 10_002].map(|i| (i.to_be_bytes(), b"data"));
+        assert!(matches!(
+            txn.bulk_load(&db, items),
+            Err(Error::OutOfOrder(2))
+        ));
+    }
+
+    #[test]
+    fn test_for_each() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for i in 0..10u64 {
+            txn.put(&db, i.to_be_bytes(), i.to_be_bytes(), WriteFlags::empty())
+                .unwrap();
+        }
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+
+        let mut sum = 0;
+        txn.for_each(&db, |_: (), v: [u8; 8]| {
+            sum += u64::from_be_bytes(v);
+            Ok(ControlFlow::Continue(()))
+        })
+        .unwrap();
+        assert_eq!(sum, 45);
+
+        let mut seen = 0;
+        txn.for_each(&db, |_: (), _: ()| {
+            seen += 1;
+            Ok(if seen == 3 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            })
+        })
+        .unwrap();
+        assert_eq!(seen, 3);
+
+        assert!(matches!(
+            txn.for_each(&db, |_: (), _: ()| Err(Error::Invalid)),
+            Err(Error::Invalid)
+        ));
+    }
+
+    #[test]
+    fn test_iter() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.iter::<(), ()>(&db).unwrap().count(), 0);
+        txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key3", b"val3", WriteFlags::empty()).unwrap();
+
+        let items = txn
+            .iter::<[u8; 4], [u8; 4]>(&db)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            items,
+            vec![
+                (*b"key1", *b"val1"),
+                (*b"key2", *b"val2"),
+                (*b"key3", *b"val3")
+            ]
+        );
+
+        // Dropping the iterator part way through closes its cursor, so the transaction can still
+        // be committed.
+        let mut iter = txn.iter::<[u8; 4], ()>(&db).unwrap();
+        assert_eq!(iter.next().unwrap().unwrap().0, *b"key1");
+        drop(iter);
+        txn.commit().unwrap();
+    }
+
+    #[test]
+    fn test_send_ro_txn() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        thread::scope(|s| {
+            s.spawn(move || {
+                let db = txn.open_db(None).unwrap();
+                assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val"));
+            });
+        });
+    }
+
+    #[test]
+    fn test_is_committed() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let mut txn = env.begin_rw_txn().unwrap();
+        assert!(!txn.is_committed());
+        {
+            let nested = txn.begin_nested_txn().unwrap();
+            assert!(!nested.is_committed());
+            nested.commit().unwrap();
+        }
+        assert!(!txn.is_committed());
+        txn.commit().unwrap();
+
+        assert!(!env.begin_ro_txn().unwrap().is_committed());
+    }
+
+    #[test]
+    fn test_into_raw() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+        let txn = unsafe { Transaction::<RW, _>::from_raw(&env, txn.into_raw()) };
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val"));
+        let raw = txn.into_raw();
+        unsafe {
+            assert!(ffi::mdbx_txn_id(raw) > 0);
+            mdbx_result(ffi::mdbx_txn_commit_ex(raw, ptr::null_mut())).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_create_db_accede() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        txn.put(&db, b"key", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key", b"val2", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        assert!(matches!(
+            txn.create_db(Some("dups"), DatabaseFlags::empty()),
+            Err(Error::Incompatible)
+        ));
+        let db = txn.create_db(Some("dups"), DatabaseFlags::ACCEDE).unwrap();
+        assert!(txn.db_flags(&db).unwrap().contains(DatabaseFlags::DUP_SORT));
+        let values = txn
+            .cursor(&db)
+            .unwrap()
+            .values_of::<[u8; 4]>(b"key")
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(values, vec![*b"val1", *b"val2"]);
+
+        // A database that doesn't exist yet is created with the remaining flags.
+        let db = txn
+            .create_db(Some("new"), DatabaseFlags::ACCEDE | DatabaseFlags::DUP_SORT)
+            .unwrap();
+        assert!(txn.db_flags(&db).unwrap().contains(DatabaseFlags::DUP_SORT));
+    }
+
+    #[test]
+    fn test_db_last_change_txn() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let a = txn.create_db(Some("a"), DatabaseFlags::empty()).unwrap();
+        let b = txn.create_db(Some("b"), DatabaseFlags::empty()).unwrap();
+        txn.put(&a, b"key", b"val", WriteFlags::empty()).unwrap();
+        txn.put(&b, b"key", b"val", WriteFlags::empty()).unwrap();
+        let first = txn.id();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let a = txn.open_db(Some("a")).unwrap();
+        assert_eq!(txn.db_last_change_txn(&a).unwrap(), first);
+        drop(txn);
+
+        // Only writing to "b" must leave "a" untouched.
+        let txn = env.begin_rw_txn().unwrap();
+        let b = txn.open_db(Some("b")).unwrap();
+        txn.put(&b, b"key", b"new", WriteFlags::empty()).unwrap();
+        let second = txn.id();
+        txn.commit().unwrap();
+        assert!(second > first);
+
+        let txn = env.begin_ro_txn().unwrap();
+        let a = txn.open_db(Some("a")).unwrap();
+        let b = txn.open_db(Some("b")).unwrap();
+        assert_eq!(txn.db_last_change_txn(&a).unwrap(), first);
+        assert_eq!(txn.db_last_change_txn(&b).unwrap(), second);
+    }
+
+    #[test]
+    fn test_get_across_put() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"old", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+
+        // Untouched by this transaction, so borrowed from the snapshot.
+        let old = txn.get::<Cow<[u8]>>(&db, b"key").unwrap().unwrap();
+        assert!(matches!(old, Cow::Borrowed(_)));
+
+        txn.put(&db, b"key", b"new", WriteFlags::empty()).unwrap();
+        for i in 0..1000u32 {
+            txn.put(&db, &i.to_be_bytes(), &[0; 64], WriteFlags::empty())
+                .unwrap();
+        }
+        assert_eq!(&*old, b"old");
+
+        // Written by this transaction, so copied out of the dirty page.
+        let new = txn.get::<Cow<[u8]>>(&db, b"key").unwrap().unwrap();
+        assert!(matches!(new, Cow::Owned(_)));
+        txn.put(&db, b"key", b"newer", WriteFlags::empty()).unwrap();
+        assert_eq!(&*new, b"new");
+    }
+
+    #[test]
+    fn test_get_unit() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"large", vec![7u8; 1 << 16], WriteFlags::empty())
+            .unwrap();
+        txn.put(&db, b"empty", b"", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get::<()>(&db, b"large").unwrap(), Some(()));
+        assert_eq!(txn.get::<()>(&db, b"empty").unwrap(), Some(()));
+        assert_eq!(txn.get::<()>(&db, b"missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_string() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, "key1", "välue", WriteFlags::empty()).unwrap();
+        txn.put(&db, "key2", [0xff, 0xfe], WriteFlags::empty())
+            .unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(
+            txn.get::<String>(&db, b"key1").unwrap(),
+            Some("välue".to_string())
+        );
+        assert!(matches!(
+            txn.get::<String>(&db, b"key2"),
+            Err(Error::DecodeError(_))
+        ));
+        assert_eq!(
+            txn.get::<[u8; 4]>(&db, b"key2").unwrap_err().to_string(),
+            "expected 4 bytes, got 2"
+        );
+    }
+
+    #[test]
+    fn test_get_array() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let hash = [0xab; 32];
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"hash", hash, WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get::<[u8; 32]>(&db, b"hash").unwrap(), Some(hash));
+        assert!(matches!(
+            txn.get::<[u8; 31]>(&db, b"hash"),
+            Err(Error::DecodeError(_))
+        ));
+        assert!(matches!(
+            txn.get::<[u8; 33]>(&db, b"hash"),
+            Err(Error::DecodeError(_))
+        ));
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn test_get_pod_value() {
+        use crate::PodValue;
+
+        #[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+        #[repr(C)]
+        struct Entry {
+            id: u64,
+            score: f32,
+            flags: u32,
+        }
+
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let entry = Entry {
+            id: u64::MAX - 3,
+            score: 0.5,
+            flags: 7,
+        };
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        // Odd key lengths leave the values unaligned in the page.
+        txn.put(&db, b"a", PodValue(entry), WriteFlags::empty())
+            .unwrap();
+        txn.put(&db, b"bcd", PodValue(entry), WriteFlags::empty())
+            .unwrap();
+        txn.put(&db, b"short", [0u8; 8], WriteFlags::empty())
+            .unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for key in [&b"a"[..], b"bcd"] {
+            assert_eq!(
+                *txn.get::<PodValue<Entry>>(&db, key).unwrap().unwrap(),
+                entry
+            );
+        }
+        assert!(matches!(
+            txn.get::<PodValue<Entry>>(&db, b"short"),
+            Err(Error::DecodeError(_))
+        ));
+    }
+
+    #[test]
+    fn test_refresh() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"old", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let mut reader = env.begin_ro_txn().unwrap();
+        let first = reader.id();
+        {
+            let db = reader.open_db(None).unwrap();
+            assert_eq!(reader.get(&db, b"key").unwrap(), Some(*b"old"));
+        }
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"new", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        {
+            let db = reader.open_db(None).unwrap();
+            assert_eq!(reader.get(&db, b"key").unwrap(), Some(*b"old"));
+        }
+
+        let id = reader.refresh().unwrap();
+        assert!(id > first);
+        assert_eq!(id, reader.id());
+        let db = reader.open_db(None).unwrap();
+        assert_eq!(reader.get(&db, b"key").unwrap(), Some(*b"new"));
+    }
+
+    #[test]
+    fn test_local_txn() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+        let id = txn.id();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn_local().unwrap();
+        assert_eq!(txn.id(), id);
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val"));
+        assert_eq!(txn.get::<()>(&db, b"missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.metrics(), TxnMetrics::default());
+
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key2", b"value2", WriteFlags::empty())
+            .unwrap();
+        txn.reserve(&db, b"key3", 10, WriteFlags::empty()).unwrap();
+        txn.get::<()>(&db, b"key1").unwrap();
+        txn.get::<()>(&db, b"missing").unwrap();
+        txn.get_multi::<()>(&db, &[b"key2", b"key3"]).unwrap();
+        assert!(txn.del(&db, b"key1", None).unwrap());
+        assert!(!txn.del(&db, b"key1", None).unwrap());
+        // Failed operations are not counted.
+        txn.put(&db, b"key2", b"x", WriteFlags::NO_OVERWRITE)
+            .unwrap_err();
+
+        assert_eq!(
+            txn.metrics(),
+            TxnMetrics {
+                puts: 3,
+                gets: 4,
+                dels: 1,
+                bytes_in: 8 + 10 + 14,
+                bytes_out: 4 + 6 + 10,
+            }
+        );
+    }
+
+    #[test]
+    fn test_value_len() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"small", b"val", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"large", &[0; 100_000], WriteFlags::empty())
+            .unwrap();
+        txn.put(&db, b"empty", b"", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.value_len(&db, b"small").unwrap(), Some(3));
+        assert_eq!(txn.value_len(&db, b"large").unwrap(), Some(100_000));
+        assert_eq!(txn.value_len(&db, b"empty").unwrap(), Some(0));
+        assert_eq!(txn.value_len(&db, b"missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_boxed() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"empty", b"", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let (boxed, shared, empty) = {
+            let txn = env.begin_ro_txn().unwrap();
+            let db = txn.open_db(None).unwrap();
+            (
+                txn.get::<Box<[u8]>>(&db, b"key").unwrap().unwrap(),
+                txn.get::<Arc<[u8]>>(&db, b"key").unwrap().unwrap(),
+                txn.get::<Arc<[u8]>>(&db, b"empty").unwrap().unwrap(),
+            )
+        };
+        assert_eq!(&*boxed, b"val");
+        assert_eq!(&*shared, b"val");
+        assert!(empty.is_empty());
+
+        let cloned = Arc::clone(&shared);
+        assert!(ptr::eq(&*cloned, &*shared));
+        assert_eq!(Arc::strong_count(&shared), 2);
+    }
+
+    #[test]
+    fn test_commit_and_begin() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        let first_id = txn.id();
+        let (_, txn) = txn.commit_and_begin().unwrap();
+        let txn = txn.unwrap();
+        assert!(txn.id() > first_id);
+
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
+        txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
+        assert_eq!(txn.get(&db, b"key2").unwrap(), Some(*b"val2"));
+    }
+
+    #[test]
+    fn test_commit_and_begin_reports_commit() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        // A failure to begin the next transaction is reported separately from the commit.
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        let (_, next) = txn.commit_and_begin_with_flags(!0).unwrap();
+        assert!(matches!(next, Err(Error::InvalidValue)));
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
+        drop(txn);
+
+        // Nested transactions are rejected before anything is committed.
+        let mut txn = env.begin_rw_txn().unwrap();
+        let nested = txn.begin_nested_txn().unwrap();
+        let db = nested.open_db(None).unwrap();
+        nested
+            .put(&db, b"key2", b"val2", WriteFlags::empty())
+            .unwrap();
+        assert!(matches!(nested.commit_and_begin(), Err(Error::BadTxn)));
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get::<()>(&db, b"key2").unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_or() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get_or(&db, b"key1", *b"none").unwrap(), *b"val1");
+        assert_eq!(txn.get_or(&db, b"key2", *b"none").unwrap(), *b"none");
+        assert_eq!(
+            txn.get_or_else(&db, b"key1", || b"none".to_vec()).unwrap(),
+            b"val1"
+        );
+        assert_eq!(
+            txn.get_or_else(&db, b"key2", || b"none".to_vec()).unwrap(),
+            b"none"
+        );
+        assert!(txn.get_or(&db, b"key1", [0u8; 2]).is_err());
+    }
+
+    #[test]
+    fn test_get_multi() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key3", b"val3", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(
+            txn.get_multi(&db, &[b"key3", b"key2", b"key1", b"key4"])
+                .unwrap(),
+            vec![Some(*b"val3"), None, Some(*b"val1"), None]
+        );
+        assert_eq!(txn.get_multi::<()>(&db, &[]).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_put_get_del_multi() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key1", b"val2", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key1", b"val3", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key2", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key2", b"val3", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key3", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key3", b"val2", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key3", b"val3", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        {
+            let mut cur = txn.cursor(&db).unwrap();
+            let iter = cur.iter_dup_of::<(), [u8; 4]>(b"key1");
+            let vals = iter.map(|x| x.unwrap()).map(|(_, x)| x).collect::<Vec<_>>();
+            assert_eq!(vals, vec![*b"val1", *b"val2", *b"val3"]);
+        }
+        txn.commit().unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.del(&db, b"key1", Some(b"val2")).unwrap();
+        txn.del(&db, b"key2", None).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        {
+            let mut cur = txn.cursor(&db).unwrap();
+            let iter = cur.iter_dup_of::<(), [u8; 4]>(b"key1");
+            let vals = iter.map(|x| x.unwrap()).map(|(_, x)| x).collect::<Vec<_>>();
+            assert_eq!(vals, vec![*b"val1", *b"val3"]);
+
+            let iter = cur.iter_dup_of::<(), ()>(b"key2");
+            assert_eq!(0, iter.count());
+        }
+        txn.commit().unwrap();
+    }
+
+    #[test]
+    fn test_put_get_del_empty_key() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.create_db(None, Default::default()).unwrap();
+        txn.put(&db, b"", b"hello", WriteFlags::empty()).unwrap();
+        assert_eq!(txn.get(&db, b"").unwrap(), Some(*b"hello"));
+        txn.commit().unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"").unwrap(), Some(*b"hello"));
+        txn.put(&db, b"", b"", WriteFlags::empty()).unwrap();
+        assert_eq!(txn.get(&db, b"").unwrap(), Some(*b""));
+    }
+
+    #[test]
+    fn test_reserve() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        {
+            let mut writer = txn.reserve(&db, b"key1", 4, WriteFlags::empty()).unwrap();
+            writer.write_all(b"val1").unwrap();
+        }
+        txn.commit().unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
+        assert_eq!(txn.get::<()>(&db, b"key").unwrap(), None);
+
+        txn.del(&db, b"key1", None).unwrap();
+        assert_eq!(txn.get::<()>(&db, b"key1").unwrap(), None);
+    }
+
+    #[test]
+    fn test_reserve_dup_sort() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
+        assert!(matches!(
+            txn.reserve(&db, b"key", 4, WriteFlags::empty()),
+            Err(Error::Incompatible)
+        ));
+        assert!(matches!(
+            txn.put_with(&db, b"key", 4, WriteFlags::empty(), |_| {}),
+            Err(Error::Incompatible)
+        ));
+        txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val"));
+    }
+
+    #[test]
+    fn test_put_batch() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let items = (0..100u32)
+            .map(|i| (i.to_be_bytes(), (i * 2).to_le_bytes()))
+            .collect::<Vec<_>>();
+        let pairs = items
+            .iter()
+            .map(|(key, data)| (&key[..], &data[..]))
+            .collect::<Vec<_>>();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put_batch(&db, &pairs, WriteFlags::empty()).unwrap();
+        assert_eq!(txn.metrics().puts, 100);
+
+        // Stops at the first failing item, keeping the ones before it.
+        let pairs: [(&[u8], &[u8]); 3] = [(b"x", b"1"), (b"y", b"2"), (b"x", b"3")];
+        assert!(matches!(
+            txn.put_batch(&db, &pairs, WriteFlags::NO_OVERWRITE),
+            Err(Error::KeyExist)
+        ));
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for (key, data) in &items {
+            assert_eq!(txn.get(&db, key).unwrap(), Some(*data));
+        }
+        assert_eq!(txn.get(&db, b"x").unwrap(), Some(*b"1"));
+        assert_eq!(txn.get(&db, b"y").unwrap(), Some(*b"2"));
+    }
+
+    #[test]
+    fn test_append_checked() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for key in [b"a", b"b", b"d"] {
+            assert_eq!(
+                txn.append_checked(&db, key, b"val").unwrap(),
+                AppendOutcome::Appended
+            );
+        }
+        assert_eq!(
+            txn.append_checked(&db, b"c", b"val").unwrap(),
+            AppendOutcome::OutOfOrder {
+                expected_gt: b"d".to_vec()
+            }
+        );
+        assert_eq!(
+            txn.append_checked(&db, b"d", b"new").unwrap(),
+            AppendOutcome::Duplicate
+        );
+
+        // Rejected items are skipped, and appending can continue.
+        assert_eq!(
+            txn.append_checked(&db, b"e", b"val").unwrap(),
+            AppendOutcome::Appended
+        );
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get::<()>(&db, b"c").unwrap(), None);
+        assert_eq!(txn.get(&db, b"d").unwrap(), Some(*b"val"));
+        assert_eq!(txn.db_stat(&db).unwrap().entries(), 4);
+    }
+
+    #[test]
+    fn test_put_with() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let parts: [&[u8]; 3] = [b"abc", b"", b"defg"];
+        let len = parts.iter().map(|p| p.len()).sum();
+        txn.put_with(&db, b"key", len, WriteFlags::empty(), |mut buf| {
+            for part in &parts {
+                buf.write_all(part).unwrap();
+            }
+            assert!(buf.is_empty());
+        })
+        .unwrap();
+        txn.put_with(&db, b"empty", 0, WriteFlags::empty(), |buf| {
+            assert!(buf.is_empty())
+        })
+        .unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"abcdefg"));
+        assert_eq!(txn.get::<Vec<u8>>(&db, b"empty").unwrap(), Some(vec![]));
+    }
+
+    #[test]
+    fn test_nested_txn() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let mut txn = env.begin_rw_txn().unwrap();
+        txn.put(
+            &txn.open_db(None).unwrap(),
+            b"key1",
+            b"val1",
+            WriteFlags::empty(),
+        )
+        .unwrap();
+
+        {
+            let nested = txn.begin_nested_txn().unwrap();
+            let db = nested.open_db(None).unwrap();
+            nested
+                .put(&db, b"key2", b"val2", WriteFlags::empty())
+                .unwrap();
+            assert_eq!(nested.get(&db, b"key1").unwrap(), Some(*b"val1"));
+            assert_eq!(nested.get(&db, b"key2").unwrap(), Some(*b"val2"));
+        }
+
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
+        assert_eq!(txn.get::<()>(&db, b"key2").unwrap(), None);
+    }
+
+    #[test]
+    fn test_nested_txn_commit() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let mut txn = env.begin_rw_txn().unwrap();
+        txn.put(
+            &txn.open_db(None).unwrap(),
+            b"key1",
+            b"val1",
+            WriteFlags::empty(),
+        )
+        .unwrap();
+
+        {
+            let nested = txn.begin_nested_txn().unwrap();
+            let db = nested.open_db(None).unwrap();
+            nested
+                .put(&db, b"key2", b"val2", WriteFlags::empty())
+                .unwrap();
+            nested.del(&db, b"key1", None).unwrap();
+            nested.commit().unwrap();
+        }
+
+        {
+            let nested = txn.begin_nested_txn().unwrap();
+            let db = nested.open_db(None).unwrap();
+            nested
+                .put(&db, b"key3", b"val3", WriteFlags::empty())
+                .unwrap();
+            // Dropping the nested transaction aborts it.
+        }
+
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get::<()>(&db, b"key1").unwrap(), None);
+        assert_eq!(txn.get(&db, b"key2").unwrap(), Some(*b"val2"));
+        assert_eq!(txn.get::<()>(&db, b"key3").unwrap(), None);
+
+        // Nothing is visible outside the parent until it commits.
+        {
+            let reader = env.begin_ro_txn().unwrap();
+            let db = reader.open_db(None).unwrap();
+            assert_eq!(reader.get::<()>(&db, b"key2").unwrap(), None);
+        }
+
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get::<()>(&db, b"key1").unwrap(), None);
+        assert_eq!(txn.get(&db, b"key2").unwrap(), Some(*b"val2"));
+        assert_eq!(txn.get::<()>(&db, b"key3").unwrap(), None);
+    }
+
+    #[test]
+    fn test_nested_txn_parent_abort() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        {
+            let mut txn = env.begin_rw_txn().unwrap();
+            let nested = txn.begin_nested_txn().unwrap();
+            nested
+                .put(
+                    &nested.open_db(None).unwrap(),
+                    b"key1",
+                    b"val1",
+                    WriteFlags::empty(),
+                )
+                .unwrap();
+            nested.commit().unwrap();
+            // Dropping the parent discards the changes merged from the nested transaction.
+        }
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.get::<()>(&db, b"key1").unwrap(), None);
+    }
+
+    #[test]
+    fn test_clear_db() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        {
+            let txn = env.begin_rw_txn().unwrap();
+            let db = txn.open_db(None).unwrap();
+            txn.put(&db, b"key", b"val", WriteFlags::empty()).unwrap();
+            for i in 0..9u32 {
+                txn.put(&db, i.to_be_bytes(), b"val", WriteFlags::empty())
+                    .unwrap();
+            }
+            assert!(!txn.commit().unwrap());
+        }
+
+        {
+            let txn = env.begin_rw_txn().unwrap();
+            assert_eq!(txn.clear_db(&txn.open_db(None).unwrap()).unwrap(), 10);
+            assert_eq!(txn.clear_db(&txn.open_db(None).unwrap()).unwrap(), 0);
+            assert!(!txn.commit().unwrap());
+        }
+
+        let txn = env.begin_ro_txn().unwrap();
+        assert_eq!(
+            txn.get::<()>(&txn.open_db(None).unwrap(), b"key").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dbi_released() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new()
+            .set_max_dbs(4)
+            .set_close_dbs(true)
+            .open(dir.path())
+            .unwrap();
+        let permanent = env
+            .open_db_permanent(Some("permanent"), DatabaseFlags::empty())
+            .unwrap();
+
+        for i in 0..100 {
+            let name = format!("db{}", i);
+            let txn = env.begin_rw_txn().unwrap();
+            txn.put(
+                &txn.create_db(Some(&name), DatabaseFlags::empty()).unwrap(),
+                b"key",
+                name.as_bytes(),
+                WriteFlags::empty(),
+            )
+            .unwrap();
+            txn.put(&permanent, name.as_bytes(), b"", WriteFlags::empty())
+                .unwrap();
+            txn.commit().unwrap();
+
+            let txn = env.begin_ro_txn().unwrap();
+            let db = txn.open_db(Some(&name)).unwrap();
+            // Opening the same database again in the transaction reuses the handle.
+            assert_eq!(txn.open_db(Some(&name)).unwrap().dbi(), db.dbi());
+            assert_eq!(
+                txn.get::<Vec<u8>>(&db, b"key").unwrap().unwrap(),
+                name.as_bytes()
+            );
+        }
+
+        let txn = env.begin_ro_txn().unwrap();
+        assert_eq!(txn.db_stat(&permanent).unwrap().entries(), 100);
+
+        // Databases opened in a nested transaction stay open until the parent ends.
+        let mut txn = env.begin_rw_txn().unwrap();
+        let nested = txn.begin_nested_txn().unwrap();
+        let db = nested
+            .create_db(Some("nested"), DatabaseFlags::empty())
+            .unwrap();
+        nested
+            .put(&db, b"key", b"val", WriteFlags::empty())
+            .unwrap();
+        nested.commit().unwrap();
+        for i in 0..10 {
+            let name = format!("db{}", i);
+            let local = env.begin_ro_txn_local().unwrap();
+            local.open_db(Some(&name)).unwrap();
+        }
+        txn.commit().unwrap();
+        let txn = env.begin_ro_txn().unwrap();
+        assert_eq!(
+            txn.get(&txn.open_db(Some("nested")).unwrap(), b"key")
+                .unwrap(),
+            Some(*b"val")
+        );
+    }
+
+    #[test]
+    fn test_get_gt() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for i in 0..10u8 {
+            txn.put(&db, [i * 2], [i], WriteFlags::empty()).unwrap();
+        }
+
+        // Paginate three keys at a time.
+        let mut pages = Vec::new();
+        let mut cursor = txn.cursor(&db).unwrap();
+        let mut next = cursor.first::<[u8; 1], [u8; 1]>().unwrap();
+        while let Some((first, _)) = next {
+            let mut page = vec![first[0]];
+            let mut last = first;
+            while page.len() < 3 {
+                match txn.get_gt::<[u8; 1], ()>(&db, &last).unwrap() {
+                    Some((key, ())) => {
+                        page.push(key[0]);
+                        last = key;
+                    }
+                    None => break,
+                }
+            }
+            pages.push(page);
+            next = txn.get_gt(&db, &last).unwrap();
+        }
+        assert_eq!(
+            pages,
+            [vec![0, 2, 4], vec![6, 8, 10], vec![12, 14, 16], vec![18]]
+        );
+
+        // Probes between, before and after the stored keys.
+        assert_eq!(txn.get_gt(&db, &[3]).unwrap(), Some(([4], [2])));
+        assert_eq!(txn.get_gt(&db, &[]).unwrap(), Some(([0], [0])));
+        assert_eq!(txn.get_gt::<(), ()>(&db, &[18]).unwrap(), None);
+        assert_eq!(txn.get_gt::<(), ()>(&db, &[19]).unwrap(), None);
+
+        let dup_db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        for (key, value) in [(b"a", b"1"), (b"a", b"2"), (b"b", b"1")] {
+            txn.put(&dup_db, key, value, WriteFlags::empty()).unwrap();
+        }
+        assert_eq!(txn.get_gt(&dup_db, b"a").unwrap(), Some((*b"b", *b"1")));
+    }
+
+    #[test]
+    fn test_get_le() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for (key, value) in [(b"key1", b"val1"), (b"key3", b"val3"), (b"key5", b"val5")] {
+            txn.put(&db, key, value, WriteFlags::empty()).unwrap();
+        }
+        assert_eq!(
+            txn.get_le(&db, b"key4").unwrap(),
+            Some((*b"key3", *b"val3"))
+        );
+        assert_eq!(
+            txn.get_le(&db, b"key1").unwrap(),
+            Some((*b"key1", *b"val1"))
+        );
+        assert_eq!(txn.get_le::<(), ()>(&db, b"key0").unwrap(), None);
+        assert_eq!(
+            txn.get_le(&db, b"key9").unwrap(),
+            Some((*b"key5", *b"val5"))
+        );
+
+        let dup_db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        for (key, value) in [(b"a", b"1"), (b"a", b"2"), (b"c", b"1"), (b"c", b"2")] {
+            txn.put(&dup_db, key, value, WriteFlags::empty()).unwrap();
+        }
+        assert_eq!(txn.get_le(&dup_db, b"b").unwrap(), Some((*b"a", *b"1")));
+        assert_eq!(txn.get_le(&dup_db, b"c").unwrap(), Some((*b"c", *b"1")));
+        assert_eq!(txn.get_le(&dup_db, b"d").unwrap(), Some((*b"c", *b"1")));
+    }
+
+    #[test]
+    fn test_get_sized() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"0123456789", WriteFlags::empty())
+            .unwrap();
+        assert_eq!(
+            txn.get_sized::<Vec<u8>>(&db, b"key").unwrap(),
+            Some((b"0123456789".to_vec(), 10))
+        );
+        assert_eq!(txn.get_sized::<()>(&db, b"missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_iter_dbs() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new()
+            .set_max_dbs(2)
+            .set_close_dbs(true)
+            .open(dir.path())
+            .unwrap();
 
         let txn = env.begin_rw_txn().unwrap();
-        let db = txn.open_db(None).unwrap();
-        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
-        assert_eq!(txn.get(&db, b"key2").unwrap(), Some(*b"val2"));
-        assert_eq!(txn.get(&db, b"key3").unwrap(), Some(*b"val3"));
-        assert_eq!(txn.get::<()>(&db, b"key").unwrap(), None);
+        let main = txn.open_db(None).unwrap();
+        txn.put(&main, b"plain", b"item", WriteFlags::empty())
+            .unwrap();
+        txn.commit().unwrap();
+        for (name, entries) in [("a", 1u8), ("b", 2), ("c", 3)] {
+            let txn = env.begin_rw_txn().unwrap();
+            let db = txn.create_db(Some(name), DatabaseFlags::empty()).unwrap();
+            for i in 0..entries {
+                txn.put(&db, [i], b"", WriteFlags::empty()).unwrap();
+            }
+            txn.commit().unwrap();
+        }
 
-        txn.del(&db, b"key1", None).unwrap();
-        assert_eq!(txn.get::<()>(&db, b"key1").unwrap(), None);
+        // Handles are only held for the duration of the listing, so two slots are enough for
+        // three databases.
+        let txn = env.begin_ro_txn().unwrap();
+        let a = txn.open_db(Some("a")).unwrap();
+        let dbs = txn.iter_dbs().unwrap();
+        assert_eq!(
+            dbs.iter()
+                .map(|(name, stat)| (name.as_str(), stat.entries()))
+                .collect::<Vec<_>>(),
+            vec![("a", 1), ("b", 2), ("c", 3)]
+        );
+        assert_eq!(txn.get(&a, &[0]).unwrap(), Some(*b""));
     }
 
     #[test]
-    fn test_put_get_del_multi() {
+    fn test_key_buf() {
         let dir = tempdir().unwrap();
         let env = Environment::new().open(dir.path()).unwrap();
 
-        let txn = env.begin_rw_txn().unwrap();
-        let db = txn.create_db(None, DatabaseFlags::DUP_SORT).unwrap();
-        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
-        txn.put(&db, b"key1", b"val2", WriteFlags::empty()).unwrap();
-        txn.put(&db, b"key1", b"val3", WriteFlags::empty()).unwrap();
-        txn.put(&db, b"key2", b"val1", WriteFlags::empty()).unwrap();
-        txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
-        txn.put(&db, b"key2", b"val3", WriteFlags::empty()).unwrap();
-        txn.put(&db, b"key3", b"val1", WriteFlags::empty()).unwrap();
-        txn.put(&db, b"key3", b"val2", WriteFlags::empty()).unwrap();
-        txn.put(&db, b"key3", b"val3", WriteFlags::empty()).unwrap();
-        txn.commit().unwrap();
-
         let txn = env.begin_rw_txn().unwrap();
         let db = txn.open_db(None).unwrap();
-        {
-            let mut cur = txn.cursor(&db).unwrap();
-            let iter = cur.iter_dup_of::<(), [u8; 4]>(b"key1");
-            let vals = iter.map(|x| x.unwrap()).map(|(_, x)| x).collect::<Vec<_>>();
-            assert_eq!(vals, vec![*b"val1", *b"val2", *b"val3"]);
+        let mut key = KeyBuf::with_capacity(5);
+        for i in 0..100u32 {
+            key.clear().push(b'k').extend(i.to_be_bytes());
+            // The value is built in the same buffer as the key.
+            txn.put(&db, &key, &key[1..], WriteFlags::empty()).unwrap();
         }
-        txn.commit().unwrap();
 
-        let txn = env.begin_rw_txn().unwrap();
-        let db = txn.open_db(None).unwrap();
-        txn.del(&db, b"key1", Some(b"val2")).unwrap();
-        txn.del(&db, b"key2", None).unwrap();
-        txn.commit().unwrap();
+        key.clear();
+        write!(key, "k").unwrap();
+        key.extend(42u32.to_be_bytes());
+        assert_eq!(&*key, b"k\0\0\0\x2a");
+        assert_eq!(txn.get(&db, &key).unwrap(), Some(42u32.to_be_bytes()));
+        assert_eq!(txn.db_stat(&db).unwrap().entries(), 100);
+    }
+
+    #[test]
+    fn test_replace() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
 
         let txn = env.begin_rw_txn().unwrap();
         let db = txn.open_db(None).unwrap();
-        {
-            let mut cur = txn.cursor(&db).unwrap();
-            let iter = cur.iter_dup_of::<(), [u8; 4]>(b"key1");
-            let vals = iter.map(|x| x.unwrap()).map(|(_, x)| x).collect::<Vec<_>>();
-            assert_eq!(vals, vec![*b"val1", *b"val3"]);
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        assert!(txn
+            .replace(&db, b"key1", b"new1", WriteFlags::empty())
+            .unwrap());
+        assert!(!txn
+            .replace(&db, b"key2", b"new2", WriteFlags::empty())
+            .unwrap());
+        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"new1"));
+        assert_eq!(txn.get::<()>(&db, b"key2").unwrap(), None);
 
-            let iter = cur.iter_dup_of::<(), ()>(b"key2");
-            assert_eq!(0, iter.count());
-        }
-        txn.commit().unwrap();
+        let dups = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        txn.put(&dups, b"key1", b"val1", WriteFlags::empty())
+            .unwrap();
+        txn.put(&dups, b"key1", b"val2", WriteFlags::empty())
+            .unwrap();
+        assert!(matches!(
+            txn.replace(&dups, b"key1", b"new1", WriteFlags::empty()),
+            Err(Error::Multival)
+        ));
+        assert!(txn
+            .replace(&dups, b"key1", b"new1", WriteFlags::ALLDUPS)
+            .unwrap());
+        assert_eq!(
+            txn.cursor(&dups)
+                .unwrap()
+                .iter_dup_of::<(), [u8; 4]>(b"key1")
+                .map(|res| res.unwrap().1)
+                .collect::<Vec<_>>(),
+            vec![*b"new1"]
+        );
     }
 
     #[test]
-    fn test_put_get_del_empty_key() {
+    fn test_count_range() {
         let dir = tempdir().unwrap();
-        let env = Environment::new().open(dir.path()).unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
 
         let txn = env.begin_rw_txn().unwrap();
-        let db = txn.create_db(None, Default::default()).unwrap();
-        txn.put(&db, b"", b"hello", WriteFlags::empty()).unwrap();
-        assert_eq!(txn.get(&db, b"").unwrap(), Some(*b"hello"));
-        txn.commit().unwrap();
+        let db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        // Key i has i + 1 data items.
+        for i in 0..5u8 {
+            for j in 0..=i {
+                txn.put(&db, [i], [j], WriteFlags::empty()).unwrap();
+            }
+        }
+        let count = |start, end| txn.count_range(&db, start, end).unwrap();
+        assert_eq!(count(Bound::Unbounded, Bound::Unbounded), 15);
+        assert_eq!(count(Bound::Included(&[1]), Bound::Excluded(&[3])), 5);
+        assert_eq!(count(Bound::Excluded(&[1]), Bound::Included(&[3])), 7);
+        assert_eq!(count(Bound::Included(&[4]), Bound::Unbounded), 5);
+        assert_eq!(count(Bound::Excluded(&[4]), Bound::Unbounded), 0);
+        assert_eq!(count(Bound::Included(&[3]), Bound::Excluded(&[3])), 0);
 
-        let txn = env.begin_rw_txn().unwrap();
         let db = txn.open_db(None).unwrap();
-        assert_eq!(txn.get(&db, b"").unwrap(), Some(*b"hello"));
-        txn.put(&db, b"", b"", WriteFlags::empty()).unwrap();
-        assert_eq!(txn.get(&db, b"").unwrap(), Some(*b""));
+        for i in 0..10u8 {
+            txn.put(&db, [i], [i], WriteFlags::empty()).unwrap();
+        }
+        assert_eq!(
+            txn.count_range(&db, Bound::Included(&[2]), Bound::Included(&[7]))
+                .unwrap(),
+            6
+        );
     }
 
     #[test]
-    fn test_reserve() {
+    fn test_count_range_key_order() {
         let dir = tempdir().unwrap();
-        let env = Environment::new().open(dir.path()).unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
 
         let txn = env.begin_rw_txn().unwrap();
-        let db = txn.open_db(None).unwrap();
-        {
-            let mut writer = txn.reserve(&db, b"key1", 4, WriteFlags::empty()).unwrap();
-            writer.write_all(b"val1").unwrap();
+        let db = txn
+            .create_db(Some("integer"), DatabaseFlags::INTEGER_KEY)
+            .unwrap();
+        for i in 1..=300u32 {
+            txn.put(&db, i.to_ne_bytes(), b"", WriteFlags::empty())
+                .unwrap();
         }
-        txn.commit().unwrap();
+        let (one, end) = (1u32.to_ne_bytes(), 256u32.to_ne_bytes());
+        assert_eq!(
+            txn.count_range(&db, Bound::Included(&one), Bound::Included(&end))
+                .unwrap(),
+            256
+        );
+        assert_eq!(
+            txn.count_range(&db, Bound::Excluded(&one), Bound::Excluded(&end))
+                .unwrap(),
+            254
+        );
+
+        let db = txn
+            .create_db(Some("reverse"), DatabaseFlags::REVERSE_KEY)
+            .unwrap();
+        for key in [b"ab", b"ba", b"ca"] {
+            txn.put(&db, key, b"", WriteFlags::empty()).unwrap();
+        }
+        assert_eq!(
+            txn.count_range(&db, Bound::Unbounded, Bound::Included(b"ca"))
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            txn.count_range(&db, Bound::Unbounded, Bound::Excluded(b"ab"))
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_swap() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
 
         let txn = env.begin_rw_txn().unwrap();
         let db = txn.open_db(None).unwrap();
-        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
-        assert_eq!(txn.get::<()>(&db, b"key").unwrap(), None);
+        assert_eq!(
+            txn.swap::<Vec<u8>>(&db, b"key", b"val1", WriteFlags::empty())
+                .unwrap(),
+            None
+        );
+        // Borrowed values stay valid after being overwritten.
+        let old = txn
+            .swap::<Cow<'_, [u8]>>(&db, b"key", b"val2", WriteFlags::empty())
+            .unwrap();
+        assert_eq!(old.as_deref(), Some(&b"val1"[..]));
+        assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"val2"));
 
-        txn.del(&db, b"key1", None).unwrap();
-        assert_eq!(txn.get::<()>(&db, b"key1").unwrap(), None);
+        let dup_db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        assert!(matches!(
+            txn.swap::<()>(&dup_db, b"key", b"val", WriteFlags::empty()),
+            Err(Error::Incompatible)
+        ));
+        assert_eq!(txn.get::<()>(&dup_db, b"key").unwrap(), None);
     }
 
     #[test]
-    fn test_nested_txn() {
+    fn test_put_current() {
         let dir = tempdir().unwrap();
-        let env = Environment::new().open(dir.path()).unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
 
-        let mut txn = env.begin_rw_txn().unwrap();
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key", b"old", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key", b"new", WriteFlags::CURRENT).unwrap();
+        assert_eq!(txn.get(&db, b"key").unwrap(), Some(*b"new"));
+        assert!(matches!(
+            txn.put(&db, b"missing", b"val", WriteFlags::CURRENT),
+            Err(Error::NotFound)
+        ));
+        assert_eq!(txn.get::<()>(&db, b"missing").unwrap(), None);
+
+        let dup_db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        txn.put(&dup_db, b"key", b"1", WriteFlags::empty()).unwrap();
+        txn.put(&dup_db, b"key", b"2", WriteFlags::CURRENT).unwrap();
+        assert_eq!(txn.get(&dup_db, b"key").unwrap(), Some(*b"2"));
+        txn.put(&dup_db, b"key", b"3", WriteFlags::empty()).unwrap();
+        assert!(matches!(
+            txn.put(&dup_db, b"key", b"4", WriteFlags::CURRENT),
+            Err(Error::Multival)
+        ));
         txn.put(
-            &txn.open_db(None).unwrap(),
-            b"key1",
-            b"val1",
-            WriteFlags::empty(),
+            &dup_db,
+            b"key",
+            b"5",
+            WriteFlags::CURRENT | WriteFlags::ALLDUPS,
         )
         .unwrap();
-
-        {
-            let nested = txn.begin_nested_txn().unwrap();
-            let db = nested.open_db(None).unwrap();
-            nested
-                .put(&db, b"key2", b"val2", WriteFlags::empty())
-                .unwrap();
-            assert_eq!(nested.get(&db, b"key1").unwrap(), Some(*b"val1"));
-            assert_eq!(nested.get(&db, b"key2").unwrap(), Some(*b"val2"));
-        }
-
-        let db = txn.open_db(None).unwrap();
-        assert_eq!(txn.get(&db, b"key1").unwrap(), Some(*b"val1"));
-        assert_eq!(txn.get::<()>(&db, b"key2").unwrap(), None);
+        let mut cursor = txn.cursor(&dup_db).unwrap();
+        assert_eq!(
+            cursor
+                .iter_dup_of::<(), [u8; 1]>(b"key")
+                .map(|item| item.unwrap().1)
+                .collect::<Vec<_>>(),
+            [*b"5"]
+        );
     }
 
     #[test]
-    fn test_clear_db() {
+    fn test_retain() {
         let dir = tempdir().unwrap();
-        let env = Environment::new().open(dir.path()).unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
 
-        {
-            let txn = env.begin_rw_txn().unwrap();
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        for i in 0..100u32 {
             txn.put(
-                &txn.open_db(None).unwrap(),
-                b"key",
-                b"val",
+                &db,
+                i.to_be_bytes(),
+                (i * 3).to_be_bytes(),
                 WriteFlags::empty(),
             )
             .unwrap();
-            assert!(!txn.commit().unwrap());
         }
+        let deleted = txn.retain(&db, |_, value| value[3] % 2 == 0).unwrap();
+        assert_eq!(deleted, 50);
+        let survivors = txn
+            .cursor(&db)
+            .unwrap()
+            .iter_start::<[u8; 4], [u8; 4]>()
+            .map(|item| u32::from_be_bytes(item.unwrap().0))
+            .collect::<Vec<_>>();
+        assert_eq!(survivors, (0..100).step_by(2).collect::<Vec<_>>());
+
+        // Consecutive deletes, running off the end of the database.
+        assert_eq!(
+            txn.retain(&db, |key, _| key < &[0, 0, 0, 10][..]).unwrap(),
+            45
+        );
+        assert_eq!(txn.db_stat(&db).unwrap().entries(), 5);
+        assert_eq!(txn.retain(&db, |_, _| false).unwrap(), 5);
+        assert_eq!(txn.retain(&db, |_, _| false).unwrap(), 0);
 
-        {
-            let txn = env.begin_rw_txn().unwrap();
-            txn.clear_db(&txn.open_db(None).unwrap()).unwrap();
-            assert!(!txn.commit().unwrap());
+        let dup_db = txn
+            .create_db(Some("dups"), DatabaseFlags::DUP_SORT)
+            .unwrap();
+        for key in [b"a", b"b"] {
+            for value in [b"1", b"2", b"3", b"4"] {
+                txn.put(&dup_db, key, value, WriteFlags::empty()).unwrap();
+            }
         }
-
-        let txn = env.begin_ro_txn().unwrap();
+        assert_eq!(txn.retain(&dup_db, |_, value| value != b"2").unwrap(), 2);
         assert_eq!(
-            txn.get::<()>(&txn.open_db(None).unwrap(), b"key").unwrap(),
-            None
+            txn.cursor(&dup_db)
+                .unwrap()
+                .iter_start::<Vec<u8>, Vec<u8>>()
+                .map(Result::unwrap)
+                .collect::<Vec<_>>(),
+            [b"a", b"b"]
+                .iter()
+                .flat_map(|key| [b"1", b"3", b"4"].map(|value| (key.to_vec(), value.to_vec())))
+                .collect::<Vec<_>>()
         );
     }
 
+    #[test]
+    fn test_integer_key() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn
+            .create_db(Some("keys"), DatabaseFlags::INTEGER_KEY)
+            .unwrap();
+        for i in [65536u64, 1, 256] {
+            txn.put(&db, i.to_ne_bytes(), b"val", WriteFlags::empty())
+                .unwrap();
+        }
+        let keys = txn
+            .cursor(&db)
+            .unwrap()
+            .iter_start::<[u8; 8], ()>()
+            .map(|r| u64::from_ne_bytes(r.unwrap().0))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![1, 256, 65536]);
+        assert!(matches!(
+            txn.put(&db, b"abc", b"val", WriteFlags::empty()),
+            Err(Error::BadValSize)
+        ));
+        assert!(matches!(
+            txn.reserve(&db, b"abcdefghi", 3, WriteFlags::empty()),
+            Err(Error::BadValSize)
+        ));
+        assert!(matches!(
+            txn.bulk_load(&db, [(u64::MAX.to_ne_bytes(), b"val")]),
+            Ok(1)
+        ));
+        assert!(matches!(
+            txn.bulk_load(&db, [(*b"abcdefghi", b"val")]),
+            Err(Error::BadValSize)
+        ));
+        assert!(matches!(
+            txn.put_batch(
+                &db,
+                &[(&2u64.to_ne_bytes(), b"val"), (b"abc", b"val")],
+                WriteFlags::empty()
+            ),
+            Err(Error::BadValSize)
+        ));
+        assert_eq!(txn.get(&db, &2u64.to_ne_bytes()).unwrap(), Some(*b"val"));
+        assert!(matches!(
+            txn.cursor(&db)
+                .unwrap()
+                .put(b"abc", b"val", WriteFlags::empty()),
+            Err(Error::BadValSize)
+        ));
+
+        let db = txn
+            .create_db(
+                Some("dups"),
+                DatabaseFlags::DUP_SORT | DatabaseFlags::DUP_FIXED | DatabaseFlags::INTEGER_DUP,
+            )
+            .unwrap();
+        assert!(txn
+            .db_flags(&db)
+            .unwrap()
+            .contains(DatabaseFlags::DUP_SORT | DatabaseFlags::INTEGER_DUP));
+        for i in [300u32, 2, 10] {
+            txn.put(&db, b"key", i.to_ne_bytes(), WriteFlags::empty())
+                .unwrap();
+        }
+        let mut cursor = txn.cursor(&db).unwrap();
+        let dups = cursor
+            .iter_dup_of::<(), [u8; 4]>(b"key")
+            .map(|r| u32::from_ne_bytes(r.unwrap().1))
+            .collect::<Vec<_>>();
+        assert_eq!(dups, vec![2, 10, 300]);
+        assert!(matches!(
+            txn.put(&db, b"key", b"abc", WriteFlags::empty()),
+            Err(Error::BadValSize)
+        ));
+    }
+
+    #[test]
+    fn test_create_db_with_compare() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(2).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn
+            .create_db_with_compare(Some("reverse"), DatabaseFlags::empty(), KeyCompare::Reverse)
+            .unwrap();
+        txn.put(&db, b"ab", b"1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"ba", b"2", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"ca", b"3", WriteFlags::empty()).unwrap();
+        let keys = txn
+            .cursor(&db)
+            .unwrap()
+            .iter_start::<[u8; 2], ()>()
+            .map(|r| r.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![*b"ba", *b"ca", *b"ab"]);
+
+        let db = txn
+            .create_db_with_compare(Some("integer"), DatabaseFlags::empty(), KeyCompare::Integer)
+            .unwrap();
+        for i in [256u32, 2, 1] {
+            txn.put(&db, i.to_ne_bytes(), b"", WriteFlags::empty())
+                .unwrap();
+        }
+        let keys = txn
+            .cursor(&db)
+            .unwrap()
+            .iter_start::<[u8; 4], ()>()
+            .map(|r| u32::from_ne_bytes(r.unwrap().0))
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![1, 2, 256]);
+        txn.commit().unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        assert!(matches!(
+            txn.create_db_with_compare(
+                Some("reverse"),
+                DatabaseFlags::empty(),
+                KeyCompare::Lexicographic
+            )
+            .unwrap_err(),
+            Error::Incompatible
+        ));
+    }
+
     #[test]
     fn test_drop_db() {
         let dir = tempdir().unwrap();
@@ -805,6 +3226,28 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_db_size() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        assert_eq!(txn.db_size(&db).unwrap(), 0);
+
+        let value = [0u8; 10_000];
+        for i in 0..20u32 {
+            txn.put(&db, i.to_be_bytes(), value, WriteFlags::empty())
+                .unwrap();
+        }
+        let stat = txn.db_stat(&db).unwrap();
+        let size = txn.db_size(&db).unwrap();
+        assert_eq!(size, stat.total_size_bytes());
+        assert_eq!(size % u64::from(stat.page_size()), 0);
+        assert!(size >= 20 * 10_000);
+        assert!(size < 2 * 20 * 10_000);
+    }
+
     #[test]
     fn test_stat() {
         let dir = tempdir().unwrap();
@@ -852,6 +3295,29 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_stat_by_name() {
+        let dir = tempdir().unwrap();
+        let env = Environment::new().set_max_dbs(1).open(dir.path()).unwrap();
+
+        let txn = env.begin_rw_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        txn.put(&db, b"key1", b"val1", WriteFlags::empty()).unwrap();
+        txn.put(&db, b"key2", b"val2", WriteFlags::empty()).unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.begin_ro_txn().unwrap();
+        let db = txn.open_db(None).unwrap();
+        let stat = txn.db_stat_by_name(None).unwrap();
+        assert_eq!(stat.entries(), txn.db_stat(&db).unwrap().entries());
+        assert_eq!(stat.entries(), 2);
+
+        assert!(matches!(
+            txn.db_stat_by_name(Some("missing")),
+            Err(Error::NotFound)
+        ));
+    }
+
     #[test]
     fn test_stat_dupsort() {
         let dir = tempdir().unwrap();